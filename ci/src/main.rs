@@ -112,6 +112,21 @@ async fn main() -> eyre::Result<()> {
         eyre::bail!("could not build cacher");
     }
 
+    // The schemas of the REST api are only derived with the openapi feature
+    let openapi_check = rust_cacher.with_exec(vec![
+        "cargo",
+        "build",
+        "-p",
+        "domain",
+        "--features",
+        "openapi",
+    ]);
+
+    let exit_code = openapi_check.exit_code().await?;
+    if exit_code != 0 {
+        eyre::bail!("could not build domain with the openapi feature");
+    }
+
     let nodejs_cacher = client.cache_volume("node");
 
     // something
@@ -171,6 +186,17 @@ async fn main() -> eyre::Result<()> {
         .with_env_variable("LEPTOS_SITE_PKG_DIR", "pkg")
         .with_env_variable("LEPTOS_SITE_ADDR", "0.0.0.0:3000")
         .with_env_variable("LEPTOS_RELOAD_PORT", "3001")
+        .with_env_variable(
+            "BITE_ARTICLE_REPO_URL",
+            "git@git.front.kjuulh.io:kjuulh/obsidian.git",
+        )
+        .with_env_variable("BITE_ARTICLE_REPO_PATH", "areas/food/events")
+        .with_env_variable("BITE_TIMEZONE", "Europe/Copenhagen")
+        .with_env_variable("BITE_ANALYTICS_DOMAIN", "bitebuds.front.kjuulh.io")
+        .with_env_variable(
+            "BITE_ANALYTICS_SCRIPT",
+            "https://plausible.front.kjuulh.io/js/script.js",
+        )
         .with_entrypoint(vec!["/app/ssr_modes"]);
 
     let image_tag = format!("docker.io/kasperhermansen/bitebuds:{tag}");
@@ -1,8 +1,7 @@
 use std::path::PathBuf;
 
-use domain::{Event, Image};
+use domain::{EventDraft, MealSlot};
 use inquire::validator::ValueRequiredValidator;
-use regex::Regex;
 
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
@@ -16,6 +15,10 @@ async fn main() -> eyre::Result<()> {
             clap::Command::new("generate")
                 .subcommand_required(true)
                 .subcommand(clap::Command::new("article")),
+        )
+        .subcommand(
+            clap::Command::new("hash-password")
+                .about("Hash a password for an author in the bitebuds config"),
         );
 
     let args = std::env::args();
@@ -28,12 +31,26 @@ async fn main() -> eyre::Result<()> {
             }
             _ => panic!("command not valid"),
         },
+        Some(("hash-password", _subm)) => {
+            hash_password()?;
+        }
         _ => panic!("command not valid"),
     }
 
     Ok(())
 }
 
+fn hash_password() -> eyre::Result<()> {
+    let password = inquire::Password::new("Password:")
+        .with_validator(ValueRequiredValidator::default())
+        .prompt()?;
+
+    // The same hashing the server verifies logins with.
+    println!("{}", services::auth::hash_password(&password)?);
+
+    Ok(())
+}
+
 async fn generate_article() -> eyre::Result<()> {
     let name = inquire::Text::new("What are you going to eat?")
         .with_validator(ValueRequiredValidator::default())
@@ -44,6 +61,16 @@ async fn generate_article() -> eyre::Result<()> {
     let time = inquire::DateSelect::new("When is the event?")
         .with_min_date(chrono::Local::now().date_naive())
         .prompt()?;
+    let meal = inquire::Select::new(
+        "Which meal is it?",
+        vec![
+            MealSlot::Breakfast,
+            MealSlot::Lunch,
+            MealSlot::Dinner,
+            MealSlot::Snack,
+        ],
+    )
+    .prompt_skippable()?;
     let cover_image = inquire::Text::new("Do you have a picture for it?")
         .prompt_skippable()?
         .and_then(|ci| if ci == "" { None } else { Some(ci) });
@@ -57,44 +84,24 @@ async fn generate_article() -> eyre::Result<()> {
         None
     };
 
-    let prepared_name = name.replace(" ", "-");
-    let prepared_name = prepared_name.replace("--", "-");
-    let prepared_name = prepared_name.trim_matches('-');
-
-    let re = Regex::new(r"[a-zA-Z-_0-9]*")?;
-    let name_slug = re
-        .find_iter(&prepared_name)
-        .map(|n| n.as_str())
-        .collect::<Vec<_>>();
-    let name_slug = name_slug.join("");
-
-    let slug = format!("{}-{}", time.format("%Y-%m-%d"), name_slug.to_lowercase());
-
-    let event = Event {
-        id: uuid::Uuid::new_v4(),
-        cover_image: cover_image.zip(cover_alt).map(|(image, alt)| Image {
-            id: uuid::Uuid::new_v4(),
-            url: image,
-            alt,
-            metadata: None,
-        }),
+    let draft = services::editor::validate(EventDraft {
         name,
         description: description.clone(),
-        time,
-        recipe_id: None,
-        images: Vec::new(),
-        metadata: None,
-    };
-
-    let contents = serde_yaml::to_string(&event)?;
-    let contents = format!(
-        "---
-{}---
-
-{}",
-        contents,
-        description.unwrap_or("".into())
-    );
+        date: time,
+        start_time: None,
+        meal,
+        servings: None,
+        tags: Vec::new(),
+        recipe: None,
+        cover_image_url: cover_image,
+        cover_image_alt: cover_alt,
+        body: description.unwrap_or_default(),
+    })?;
+    let slug = services::editor::slug(draft.date, &draft.name);
+
+    // The same front matter the editor on the site writes, and which the
+    // server reads.
+    let contents = services::editor::render_file(None, &draft)?;
 
     let mut vault_path = PathBuf::from(std::env::var("BITEME_ROOT").unwrap());
     vault_path.push(format!("areas/food/events/{}.md", slug));
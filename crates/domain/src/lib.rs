@@ -2,17 +2,125 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+pub mod scaling;
+pub mod units;
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Metadata(HashMap<String, String>);
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+impl From<HashMap<String, String>> for Metadata {
+    fn from(value: HashMap<String, String>) -> Self {
+        Self(value)
+    }
+}
+
+impl Metadata {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Recipe {
     pub id: uuid::Uuid,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// How many people the recipe as written feeds.
+    pub servings: Option<u32>,
+    pub prep_time_minutes: Option<u32>,
+    pub cook_time_minutes: Option<u32>,
+    pub ingredients: Vec<Ingredient>,
+    /// Steps in the order they should be carried out.
+    pub steps: Vec<Step>,
+    pub references: Vec<Reference>,
+    pub images: Vec<Image>,
     pub metadata: Option<Metadata>,
+    /// The markdown body of the recipe, rendered to sanitized html.
+    pub body_html: Option<String>,
+}
+
+impl Recipe {
+    pub fn total_time_minutes(&self) -> Option<u32> {
+        match (self.prep_time_minutes, self.cook_time_minutes) {
+            (None, None) => None,
+            (prep, cook) => Some(prep.unwrap_or(0) + cook.unwrap_or(0)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Ingredient {
+    pub name: String,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    /// Free text such as "finely chopped" or "to taste".
+    pub note: Option<String>,
+}
+
+impl Ingredient {
+    /// Reads an ingredient written on a single line, such as `2 spsk smør` or
+    /// `1½ dl fløde, pisket`. Text after a comma is kept as a note. Units are
+    /// only read after an amount, apart from `knsp`, so `gram masala` stays a
+    /// name.
+    pub fn parse(line: &str) -> Self {
+        let (text, note) = match note_start(line) {
+            Some(comma) => (&line[..comma], Some(line[comma + 1..].trim().to_string())),
+            None => (line, None),
+        };
+
+        let (quantity, rest) = units::split_quantity(text);
+        let unit = units::split_unit(rest).filter(|(unit, name)| {
+            !name.is_empty() && (quantity.is_some() || *unit == units::Unit::Pinch)
+        });
+
+        let (unit, name) = match unit {
+            Some((unit, name)) => (Some(unit.symbol().to_string()), name),
+            None => (None, rest),
+        };
+
+        Self {
+            name: name.trim().to_string(),
+            quantity,
+            unit,
+            note: note.filter(|note| !note.is_empty()),
+        }
+    }
+}
+
+/// The first comma which isn't a decimal comma, as in `1,5 dl`.
+fn note_start(line: &str) -> Option<usize> {
+    line.char_indices()
+        .find(|&(i, c)| {
+            c == ','
+                && !(line[..i].ends_with(|d: char| d.is_ascii_digit())
+                    && line[i + 1..].starts_with(|d: char| d.is_ascii_digit()))
+        })
+        .map(|(i, _)| i)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Step {
+    pub text: String,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+    pub title: Option<String>,
+    pub url: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Image {
     pub id: uuid::Uuid,
@@ -22,36 +130,343 @@ pub struct Image {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Event {
     pub id: uuid::Uuid,
+    pub slug: String,
     pub cover_image: Option<Image>,
     pub name: String,
     pub description: Option<String>,
     pub time: chrono::NaiveDate,
+    /// Time of day the meal is served, if it has been decided.
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, example = "18:30:00"))]
+    pub start_time: Option<chrono::NaiveTime>,
+    pub duration_minutes: Option<u32>,
+    pub meal: Option<MealSlot>,
+    /// How many people the meal is planned for. The recipe is scaled to it on
+    /// shopping lists.
+    pub servings: Option<u32>,
+    /// Set on events which repeat. They are expanded into occurrences before
+    /// being shown.
+    pub recurrence: Option<Recurrence>,
+    /// For an occurrence of a recurring event, the id of that event.
+    pub series_id: Option<uuid::Uuid>,
+    /// Free form labels such as `vegetarian` or `guests`.
+    #[serde(default)]
+    pub tags: Vec<String>,
     pub recipe_id: Option<uuid::Uuid>,
     pub images: Vec<Image>,
     pub metadata: Option<Metadata>,
+    /// The markdown body of the event, rendered to sanitized html.
+    pub body_html: Option<String>,
+}
+
+/// How an event repeats, as an RFC 5545 RRULE such as `FREQ=WEEKLY;BYDAY=FR`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Recurrence {
+    pub rule: String,
+    /// Dates on which the event is skipped.
+    pub except: Vec<chrono::NaiveDate>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct EventOverview {
     pub id: uuid::Uuid,
+    pub slug: String,
     pub cover_image: Option<Image>,
     pub name: String,
     pub description: Option<String>,
     pub time: chrono::NaiveDate,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, example = "18:30:00"))]
+    pub start_time: Option<chrono::NaiveTime>,
+    pub duration_minutes: Option<u32>,
+    pub meal: Option<MealSlot>,
+}
+
+impl Event {
+    /// See [`EventOverview::sort_key`].
+    pub fn sort_key(&self) -> (chrono::NaiveDate, Option<chrono::NaiveTime>) {
+        sort_key(self.time, self.start_time, self.meal)
+    }
+}
+
+impl EventOverview {
+    /// Orders events by date, then by time of day. Events without a time of
+    /// day are placed at the usual time of their meal, or first if they have
+    /// neither.
+    pub fn sort_key(&self) -> (chrono::NaiveDate, Option<chrono::NaiveTime>) {
+        sort_key(self.time, self.start_time, self.meal)
+    }
+}
+
+fn sort_key(
+    date: chrono::NaiveDate,
+    start_time: Option<chrono::NaiveTime>,
+    meal: Option<MealSlot>,
+) -> (chrono::NaiveDate, Option<chrono::NaiveTime>) {
+    (date, start_time.or_else(|| meal.map(|m| m.usual_time())))
 }
 
 impl From<Event> for EventOverview {
     fn from(value: Event) -> Self {
         Self {
             id: value.id,
+            slug: value.slug,
             cover_image: value.cover_image,
             name: value.name,
             description: value.description,
             time: value.time,
+            start_time: value.start_time,
+            duration_minutes: value.duration_minutes,
+            meal: value.meal,
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum MealSlot {
+    Breakfast,
+    Lunch,
+    Dinner,
+    Snack,
+}
+
+impl MealSlot {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MealSlot::Breakfast => "Breakfast",
+            MealSlot::Lunch => "Lunch",
+            MealSlot::Dinner => "Dinner",
+            MealSlot::Snack => "Snack",
+        }
+    }
+
+    /// When the meal is usually served, used for ordering meals which don't
+    /// have a time of day.
+    pub fn usual_time(&self) -> chrono::NaiveTime {
+        let (hour, minute) = match self {
+            MealSlot::Breakfast => (8, 0),
+            MealSlot::Lunch => (12, 0),
+            MealSlot::Snack => (15, 0),
+            MealSlot::Dinner => (18, 0),
+        };
+
+        chrono::NaiveTime::from_hms_opt(hour, minute, 0).expect("valid time of day")
+    }
+}
+
+impl std::fmt::Display for MealSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Whether a guest is joining a meal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Attendance {
+    Attending,
+    Maybe,
+    Declined,
+}
+
+impl Attendance {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Attendance::Attending => "Attending",
+            Attendance::Maybe => "Maybe",
+            Attendance::Declined => "Declined",
+        }
+    }
+}
+
+impl std::fmt::Display for Attendance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// A guest's answer to whether they are joining a meal. A guest answers once
+/// per event, identified by their name, and can change the answer later.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rsvp {
+    pub id: uuid::Uuid,
+    pub event_id: uuid::Uuid,
+    pub name: String,
+    pub attendance: Attendance,
+    /// How many people the answer is for, including the guest.
+    pub headcount: u32,
+    pub note: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Everyone who has answered for an event.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attendees {
+    pub rsvps: Vec<Rsvp>,
+}
+
+impl Attendees {
+    /// How many people gave the answer, counting everyone a guest answered
+    /// for.
+    pub fn headcount(&self, attendance: Attendance) -> u32 {
+        self.rsvps
+            .iter()
+            .filter(|r| r.attendance == attendance)
+            .map(|r| r.headcount)
+            .sum()
+    }
+}
+
+/// A logged in author. The csrf token has to be sent back with every
+/// privileged action.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorSession {
+    pub name: String,
+    pub csrf_token: String,
+}
+
+/// The parts of an event's markdown file an author edits on the site. Front
+/// matter the editor doesn't know about is left as it is.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDraft {
+    pub name: String,
+    pub description: Option<String>,
+    pub date: chrono::NaiveDate,
+    pub start_time: Option<chrono::NaiveTime>,
+    pub meal: Option<MealSlot>,
+    pub servings: Option<u32>,
+    pub tags: Vec<String>,
+    /// Slug of the linked recipe.
+    pub recipe: Option<String>,
+    pub cover_image_url: Option<String>,
+    pub cover_image_alt: Option<String>,
+    /// The markdown body below the front matter.
+    pub body: String,
+}
+
+/// An event as loaded into the editor.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditableEvent {
+    pub slug: String,
+    pub draft: EventDraft,
+    /// Commit of the content repository the draft was read from. Saving fails
+    /// if the file has changed since.
+    pub base_commit: Option<String>,
+}
+
+/// Everything needed for the meals planned between `from` and `until`, both
+/// included.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShoppingList {
+    pub from: chrono::NaiveDate,
+    pub until: chrono::NaiveDate,
+    /// Ordered by name.
+    pub items: Vec<ShoppingItem>,
+}
+
+/// An ingredient summed over every recipe it is used in. The same ingredient
+/// is listed more than once when it is measured in ways which can't be added,
+/// such as by weight and by volume.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShoppingItem {
+    /// Identifies the item within the list, also when the plan changes.
+    pub key: String,
+    pub name: String,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    /// Names of the recipes the ingredient is for.
+    pub recipes: Vec<String>,
+    pub notes: Vec<String>,
+    /// Whether someone has bought it.
+    pub checked: bool,
+}
+
+/// The meals planned for a single day.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayOverview {
+    pub date: chrono::NaiveDate,
+    pub events: Vec<EventOverview>,
+}
+
+impl DayOverview {
+    /// Groups events by their date, ordering both the days and the meals on
+    /// each day.
+    pub fn group(mut events: Vec<EventOverview>) -> Vec<DayOverview> {
+        events.sort_by_key(|e| e.sort_key());
+
+        let mut days: Vec<DayOverview> = Vec::new();
+        for event in events {
+            match days.last_mut() {
+                Some(day) if day.date == event.time => day.events.push(event),
+                _ => days.push(DayOverview {
+                    date: event.time,
+                    events: vec![event],
+                }),
+            }
+        }
+
+        days
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// A page of events from a date range query.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EventPage {
+    pub events: Vec<EventOverview>,
+    /// Pass this to the next query to get the following page, `None` on the
+    /// last page.
+    pub next_cursor: Option<String>,
+}
+
+/// Something wrong with a file in the content repository, such as invalid
+/// front matter. The file is skipped, and the diagnostic is kept around so the
+/// author can find out why.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseDiagnostic {
+    pub path: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+/// Health of the synchronisation with the content repository.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub last_success_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Commit of the content repository the current content was loaded from.
+    pub last_commit: Option<String>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub consecutive_failures: u32,
+    /// While failing, syncs are held back until this time.
+    pub next_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+}
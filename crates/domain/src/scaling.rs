@@ -0,0 +1,129 @@
+use crate::units::{self, Unit};
+use crate::{Ingredient, Recipe};
+
+/// Notes which mean the amount is a matter of taste, and so isn't scaled.
+const TO_TASTE: &[&str] = &["to taste", "efter smag", "efter behov"];
+
+impl Recipe {
+    /// The recipe for `servings` people instead of the number it is written
+    /// for. Recipes which don't say how many they feed are returned as they
+    /// are.
+    pub fn scaled_to(&self, servings: u32) -> Recipe {
+        let Some(written_for) = self.servings.filter(|s| *s > 0) else {
+            return self.clone();
+        };
+        let factor = servings as f64 / written_for as f64;
+
+        Recipe {
+            servings: Some(servings),
+            ingredients: self.ingredients.iter().map(|i| i.scaled(factor)).collect(),
+            ..self.clone()
+        }
+    }
+}
+
+impl Ingredient {
+    /// Whether the amount follows the number of servings. Pinches and
+    /// ingredients added to taste stay as they are written.
+    pub fn scales(&self) -> bool {
+        let to_taste = self.note.as_ref().is_some_and(|note| {
+            let note = note.to_lowercase();
+            TO_TASTE.iter().any(|phrase| note.contains(phrase))
+        });
+        let unit = self.unit.as_deref().and_then(Unit::parse);
+
+        self.quantity.is_some() && !to_taste && unit != Some(Unit::Pinch)
+    }
+
+    /// Multiplies the amount by `factor`. Weights and volumes are given in
+    /// the metric unit which reads best afterwards, while things which are
+    /// counted, such as `2 løg` or `1 dåse tomater`, are rounded up to the
+    /// nearest half.
+    pub fn scaled(&self, factor: f64) -> Ingredient {
+        let Some(quantity) = self.quantity.filter(|_| self.scales()) else {
+            return self.clone();
+        };
+        let quantity = quantity * factor;
+
+        let (quantity, unit) = match self.unit.as_deref().and_then(Unit::parse) {
+            Some(unit) if unit.dimension().is_some() && unit.is_metric() => {
+                let (quantity, unit) = units::normalize(quantity, unit);
+                (quantity, Some(unit.symbol().to_string()))
+            }
+            Some(_) => (quantity, self.unit.clone()),
+            None => ((quantity * 2.0).ceil() / 2.0, self.unit.clone()),
+        };
+
+        Ingredient {
+            quantity: Some(quantity),
+            unit,
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Ingredient, Recipe};
+
+    fn recipe(servings: Option<u32>) -> Recipe {
+        Recipe {
+            id: uuid::Uuid::new_v4(),
+            slug: "gammeldags-oksesteg".into(),
+            name: "Gammeldags oksesteg".into(),
+            description: None,
+            servings,
+            prep_time_minutes: None,
+            cook_time_minutes: None,
+            ingredients: vec![
+                Ingredient::parse("500 g oksesteg"),
+                Ingredient::parse("2 spsk smør"),
+                Ingredient::parse("1 løg"),
+                Ingredient::parse("1 knsp muskat"),
+                Ingredient::parse("1 tsk salt, efter smag"),
+                Ingredient::parse("peber"),
+            ],
+            steps: vec![],
+            references: vec![],
+            images: vec![],
+            metadata: None,
+            body_html: None,
+        }
+    }
+
+    fn amount(recipe: &Recipe, name: &str) -> (Option<f64>, Option<String>) {
+        let ingredient = recipe.ingredients.iter().find(|i| i.name == name).unwrap();
+        (ingredient.quantity, ingredient.unit.clone())
+    }
+
+    #[test]
+    fn ingredients_follow_the_servings() {
+        let scaled = recipe(Some(2)).scaled_to(5);
+
+        assert_eq!(scaled.servings, Some(5));
+        assert_eq!(amount(&scaled, "oksesteg"), (Some(1.25), Some("kg".into())));
+        assert_eq!(amount(&scaled, "smør"), (Some(5.0), Some("spsk".into())));
+        assert_eq!(amount(&scaled, "løg"), (Some(2.5), None));
+
+        // Things which are counted are rounded up to the nearest half.
+        let scaled = recipe(Some(4)).scaled_to(3);
+        assert_eq!(amount(&scaled, "løg"), (Some(1.0), None));
+        assert_eq!(amount(&scaled, "oksesteg"), (Some(375.0), Some("g".into())));
+    }
+
+    #[test]
+    fn pinches_and_seasoning_to_taste_are_not_scaled() {
+        let scaled = recipe(Some(2)).scaled_to(6);
+
+        assert_eq!(amount(&scaled, "muskat"), (Some(1.0), Some("knsp".into())));
+        assert_eq!(amount(&scaled, "salt"), (Some(1.0), Some("tsk".into())));
+        assert_eq!(amount(&scaled, "peber"), (None, None));
+    }
+
+    #[test]
+    fn recipes_without_servings_are_kept_as_written() {
+        let unscaled = recipe(None);
+        assert_eq!(unscaled.scaled_to(6).ingredients, unscaled.ingredients);
+        assert_eq!(unscaled.scaled_to(6).servings, None);
+    }
+}
@@ -0,0 +1,361 @@
+use std::fmt;
+
+/// What a unit measures. Units can only be converted into units which measure
+/// the same.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    Weight,
+    Volume,
+}
+
+/// Units found in Danish and English recipes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Gram,
+    Kilogram,
+    Millilitre,
+    Centilitre,
+    Decilitre,
+    Litre,
+    Teaspoon,
+    Tablespoon,
+    /// A knife tip, `knsp`. It is too small to be measured.
+    Pinch,
+    Cup,
+    FluidOunce,
+    Ounce,
+    Pound,
+}
+
+impl Unit {
+    /// Reads a unit as written in recipes, such as `spsk`, `tbsp` or `Gram`.
+    pub fn parse(unit: &str) -> Option<Unit> {
+        let unit = unit.trim().trim_end_matches('.').to_lowercase();
+
+        let unit = match unit.as_str() {
+            "g" | "gr" | "gram" => Unit::Gram,
+            "kg" | "kilo" | "kilogram" => Unit::Kilogram,
+            "ml" | "milliliter" | "millilitre" => Unit::Millilitre,
+            "cl" | "centiliter" | "centilitre" => Unit::Centilitre,
+            "dl" | "deciliter" | "decilitre" => Unit::Decilitre,
+            "l" | "liter" | "litre" => Unit::Litre,
+            "tsk" | "teske" | "tsp" | "teaspoon" | "teaspoons" => Unit::Teaspoon,
+            "spsk" | "spiseske" | "tbsp" | "tablespoon" | "tablespoons" => Unit::Tablespoon,
+            "knsp" | "knivspids" | "pinch" => Unit::Pinch,
+            "cup" | "cups" => Unit::Cup,
+            "fl oz" | "fl. oz" | "floz" => Unit::FluidOunce,
+            "oz" | "ounce" | "ounces" => Unit::Ounce,
+            "lb" | "lbs" | "pound" | "pounds" => Unit::Pound,
+            _ => return None,
+        };
+
+        Some(unit)
+    }
+
+    /// How the unit is written in Danish recipes, or in English ones for
+    /// units Danish recipes don't use.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Gram => "g",
+            Unit::Kilogram => "kg",
+            Unit::Millilitre => "ml",
+            Unit::Centilitre => "cl",
+            Unit::Decilitre => "dl",
+            Unit::Litre => "l",
+            Unit::Teaspoon => "tsk",
+            Unit::Tablespoon => "spsk",
+            Unit::Pinch => "knsp",
+            Unit::Cup => "cup",
+            Unit::FluidOunce => "fl oz",
+            Unit::Ounce => "oz",
+            Unit::Pound => "lb",
+        }
+    }
+
+    pub fn dimension(&self) -> Option<Dimension> {
+        match self {
+            Unit::Gram | Unit::Kilogram | Unit::Ounce | Unit::Pound => Some(Dimension::Weight),
+            Unit::Pinch => None,
+            _ => Some(Dimension::Volume),
+        }
+    }
+
+    pub fn is_metric(&self) -> bool {
+        !matches!(
+            self,
+            Unit::Cup | Unit::FluidOunce | Unit::Ounce | Unit::Pound
+        )
+    }
+
+    /// Size in grams or millilitres, using US cups and ounces.
+    fn size(&self) -> Option<f64> {
+        let size = match self {
+            Unit::Gram | Unit::Millilitre => 1.0,
+            Unit::Kilogram | Unit::Litre => 1000.0,
+            Unit::Centilitre => 10.0,
+            Unit::Decilitre => 100.0,
+            Unit::Teaspoon => 5.0,
+            Unit::Tablespoon => 15.0,
+            Unit::Cup => 236.6,
+            Unit::FluidOunce => 29.57,
+            Unit::Ounce => 28.35,
+            Unit::Pound => 453.6,
+            Unit::Pinch => return None,
+        };
+
+        Some(size)
+    }
+
+    /// Converts `amount` of this unit into `other`, if they measure the same.
+    pub fn convert(&self, amount: f64, other: Unit) -> Option<f64> {
+        if self.dimension()? != other.dimension()? {
+            return None;
+        }
+
+        Some(amount * self.size()? / other.size()?)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Converts imperial units into metric ones, and picks the unit which reads
+/// best for the amount, so `1500 g` becomes `1.5 kg` and `6 tsk` becomes
+/// `2 spsk`. Spoons stay spoons until they add up to a decilitre.
+pub fn normalize(amount: f64, unit: Unit) -> (f64, Unit) {
+    let target = match unit.dimension() {
+        None => return (amount, unit),
+        Some(Dimension::Weight) => match unit.convert(amount, Unit::Gram) {
+            Some(grams) if grams >= 1000.0 => Unit::Kilogram,
+            _ => Unit::Gram,
+        },
+        Some(Dimension::Volume) => {
+            let millilitres = unit.convert(amount, Unit::Millilitre).unwrap_or_default();
+            let spoons = matches!(unit, Unit::Teaspoon | Unit::Tablespoon);
+
+            match millilitres {
+                ml if ml >= 1000.0 => Unit::Litre,
+                ml if ml >= 100.0 || (ml >= 50.0 && !spoons) => Unit::Decilitre,
+                ml if spoons && ml >= 15.0 => Unit::Tablespoon,
+                _ if spoons => Unit::Teaspoon,
+                _ => Unit::Millilitre,
+            }
+        }
+    };
+
+    match unit.convert(amount, target) {
+        Some(converted) => (converted, target),
+        None => (amount, unit),
+    }
+}
+
+const FRACTIONS: &[(f64, char)] = &[
+    (0.125, '⅛'),
+    (0.25, '¼'),
+    (1.0 / 3.0, '⅓'),
+    (0.5, '½'),
+    (2.0 / 3.0, '⅔'),
+    (0.75, '¾'),
+];
+
+/// Writes an amount the way it is written in recipes: common fractions such
+/// as `1½` for small amounts, and whole numbers for larger ones.
+pub fn format_quantity(amount: f64) -> String {
+    if amount >= 100.0 {
+        return ((amount / 5.0).round() * 5.0).to_string();
+    }
+    if amount >= 10.0 {
+        return amount.round().to_string();
+    }
+
+    let whole = amount.trunc();
+    let fraction = amount - whole;
+    if fraction < 0.02 {
+        return whole.to_string();
+    }
+    if fraction > 0.98 {
+        return (whole + 1.0).to_string();
+    }
+
+    match FRACTIONS
+        .iter()
+        .find(|(value, _)| (fraction - value).abs() < 0.02)
+    {
+        Some((_, glyph)) if whole == 0.0 => glyph.to_string(),
+        Some((_, glyph)) => format!("{whole}{glyph}"),
+        None if amount < 1.0 => ((amount * 100.0).round() / 100.0).to_string(),
+        None => ((amount * 10.0).round() / 10.0).to_string(),
+    }
+}
+
+/// Reads an amount from the start of `text`, such as `2`, `1,5`, `1½`,
+/// `1 1/2` or `2-3`, and returns it with the rest of the text. Ranges are
+/// read as their largest amount, which is what should be bought.
+pub fn split_quantity(text: &str) -> (Option<f64>, &str) {
+    let Some((mut amount, mut rest)) = read_number(text.trim_start()) else {
+        return (None, text.trim());
+    };
+
+    // A whole number followed by a fraction, as in `1 1/2`.
+    if amount.fract() == 0.0 {
+        if let Some((fraction, after)) = read_number(rest.trim_start()).filter(|(f, _)| *f < 1.0) {
+            amount += fraction;
+            rest = after;
+        }
+    }
+
+    if let Some(upper) = rest
+        .trim_start()
+        .strip_prefix(['-', '–'])
+        .and_then(|after| read_number(after.trim_start()))
+    {
+        (amount, rest) = upper;
+    }
+
+    (Some(amount), rest.trim_start())
+}
+
+/// Reads a unit from the start of `text`, and returns it with the rest of
+/// the text.
+pub fn split_unit(text: &str) -> Option<(Unit, &str)> {
+    let text = text.trim_start();
+    let mut words = text.splitn(3, char::is_whitespace);
+    let first = words.next()?;
+
+    // Units of two words, such as `fl oz`.
+    if let Some(second) = words.next() {
+        let length = first.len() + 1 + second.len();
+        if let Some(unit) = Unit::parse(&text[..length]) {
+            return Some((unit, text[length..].trim_start()));
+        }
+    }
+
+    Unit::parse(first).map(|unit| (unit, text[first.len()..].trim_start()))
+}
+
+/// A single number, a fraction such as `½` or `1/2`, or a number followed by
+/// a fraction such as `1½`. Both `,` and `.` are read as decimal points.
+fn read_number(text: &str) -> Option<(f64, &str)> {
+    let end = text
+        .char_indices()
+        .find(|&(i, c)| {
+            let decimal_point = (c == ',' || c == '.')
+                && i > 0
+                && text[i + 1..].starts_with(|d: char| d.is_ascii_digit());
+            !(c.is_ascii_digit() || decimal_point)
+        })
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+
+    let mut amount = match end {
+        0 => None,
+        _ => Some(text[..end].replace(',', ".").parse::<f64>().ok()?),
+    };
+    let mut rest = &text[end..];
+
+    if let Some((value, glyph)) = rest
+        .chars()
+        .next()
+        .and_then(|c| FRACTIONS.iter().find(|(_, glyph)| *glyph == c))
+    {
+        amount = Some(amount.unwrap_or_default() + value);
+        rest = &rest[glyph.len_utf8()..];
+    } else if let (Some(numerator), Some(after_slash)) = (amount, rest.strip_prefix('/')) {
+        let end = after_slash
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after_slash.len());
+        let denominator = after_slash[..end].parse::<f64>().ok()?;
+        if denominator == 0.0 || numerator.fract() != 0.0 {
+            return None;
+        }
+        amount = Some(numerator / denominator);
+        rest = &after_slash[end..];
+    }
+
+    amount.map(|amount| (amount, rest))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Ingredient;
+
+    use super::{format_quantity, normalize, split_quantity, split_unit, Unit};
+
+    #[test]
+    fn quantities_are_read_as_written() {
+        assert_eq!(split_quantity("2 spsk smør"), (Some(2.0), "spsk smør"));
+        assert_eq!(split_quantity("1½ dl fløde"), (Some(1.5), "dl fløde"));
+        assert_eq!(split_quantity("1,5 dl fløde"), (Some(1.5), "dl fløde"));
+        assert_eq!(split_quantity("1 1/2 dl fløde"), (Some(1.5), "dl fløde"));
+        assert_eq!(split_quantity("½ tsk salt"), (Some(0.5), "tsk salt"));
+        assert_eq!(split_quantity("500g oksesteg"), (Some(500.0), "g oksesteg"));
+        assert_eq!(split_quantity("2-3 løg"), (Some(3.0), "løg"));
+        assert_eq!(split_quantity("salt"), (None, "salt"));
+        assert_eq!(split_quantity("1/0 løg").0, None);
+    }
+
+    #[test]
+    fn units_are_read_in_danish_and_english() {
+        assert_eq!(split_unit("spsk smør"), Some((Unit::Tablespoon, "smør")));
+        assert_eq!(
+            split_unit("Tbsp. butter"),
+            Some((Unit::Tablespoon, "butter"))
+        );
+        assert_eq!(split_unit("fl oz cream"), Some((Unit::FluidOunce, "cream")));
+        assert_eq!(split_unit("knsp salt"), Some((Unit::Pinch, "salt")));
+        assert_eq!(split_unit("løg"), None);
+    }
+
+    #[test]
+    fn ingredient_lines_are_split_into_amount_unit_and_name() {
+        let smør = Ingredient::parse("2 spsk smør");
+        assert_eq!(smør.quantity, Some(2.0));
+        assert_eq!(smør.unit.as_deref(), Some("spsk"));
+        assert_eq!(smør.name, "smør");
+
+        let fløde = Ingredient::parse("1,5 Deciliter fløde, pisket");
+        assert_eq!(fløde.quantity, Some(1.5));
+        assert_eq!(fløde.unit.as_deref(), Some("dl"));
+        assert_eq!(fløde.name, "fløde");
+        assert_eq!(fløde.note.as_deref(), Some("pisket"));
+
+        let løg = Ingredient::parse("2 løg, groft hakket");
+        assert_eq!((løg.quantity, løg.unit), (Some(2.0), None));
+        assert_eq!(løg.name, "løg");
+
+        let salt = Ingredient::parse("knsp salt");
+        assert_eq!((salt.quantity, salt.unit.as_deref()), (None, Some("knsp")));
+
+        let masala = Ingredient::parse("gram masala");
+        assert_eq!((masala.unit, masala.name.as_str()), (None, "gram masala"));
+    }
+
+    #[test]
+    fn amounts_are_converted_to_the_unit_which_reads_best() {
+        assert_eq!(normalize(1500.0, Unit::Gram), (1.5, Unit::Kilogram));
+        assert_eq!(normalize(0.5, Unit::Litre), (5.0, Unit::Decilitre));
+        assert_eq!(normalize(6.0, Unit::Teaspoon), (2.0, Unit::Tablespoon));
+        assert_eq!(normalize(4.0, Unit::Tablespoon), (4.0, Unit::Tablespoon));
+        assert_eq!(normalize(2.0, Unit::Pinch), (2.0, Unit::Pinch));
+
+        let (amount, unit) = normalize(1.0, Unit::Pound);
+        assert_eq!(unit, Unit::Gram);
+        assert!((amount - 453.6).abs() < 0.01);
+        assert_eq!(Unit::Gram.convert(1.0, Unit::Decilitre), None);
+    }
+
+    #[test]
+    fn quantities_are_written_with_fractions() {
+        assert_eq!(format_quantity(1.5), "1½");
+        assert_eq!(format_quantity(0.25), "¼");
+        assert_eq!(format_quantity(4.0 / 3.0), "1⅓");
+        assert_eq!(format_quantity(0.1 + 0.2), "0.3");
+        assert_eq!(format_quantity(1.2), "1.2");
+        assert_eq!(format_quantity(2.999), "3");
+        assert_eq!(format_quantity(12.4), "12");
+        assert_eq!(format_quantity(453.6), "455");
+    }
+}
@@ -0,0 +1,19 @@
+-- Content mirrored from the content repository. Events and recipes are stored
+-- as json, with the columns needed for querying pulled out next to them.
+CREATE TABLE events (
+    id TEXT PRIMARY KEY NOT NULL,
+    slug TEXT NOT NULL,
+    time TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+
+CREATE INDEX events_slug ON events (slug);
+CREATE INDEX events_time ON events (time);
+
+CREATE TABLE recipes (
+    id TEXT PRIMARY KEY NOT NULL,
+    slug TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+
+CREATE INDEX recipes_slug ON recipes (slug);
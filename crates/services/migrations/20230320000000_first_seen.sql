@@ -0,0 +1,6 @@
+-- When each event was first mirrored, in unix seconds, so feeds can list
+-- events in the order they were planned. Events mirrored before this column
+-- existed are counted as seen now.
+ALTER TABLE events ADD COLUMN first_seen_at INTEGER NOT NULL DEFAULT 0;
+
+UPDATE events SET first_seen_at = CAST(strftime('%s', 'now') AS INTEGER);
@@ -0,0 +1,13 @@
+-- Answers from guests on whether they join a meal. A guest is identified by
+-- their name, so `name_key` is the lowercased name.
+CREATE TABLE rsvps (
+    id TEXT PRIMARY KEY NOT NULL,
+    event_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    name_key TEXT NOT NULL,
+    attendance TEXT NOT NULL,
+    headcount INTEGER NOT NULL,
+    note TEXT,
+    updated_at INTEGER NOT NULL,
+    UNIQUE (event_id, name_key)
+);
@@ -0,0 +1,7 @@
+-- Logged in authors. Only a hash of the session token is stored.
+CREATE TABLE sessions (
+    token_hash TEXT PRIMARY KEY NOT NULL,
+    author TEXT NOT NULL,
+    csrf_token TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
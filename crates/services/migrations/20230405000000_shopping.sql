@@ -0,0 +1,8 @@
+-- Items checked off on shopping lists. A list is identified by its first day,
+-- and an item by its key within the list.
+CREATE TABLE shopping_checks (
+    list_from TEXT NOT NULL,
+    item_key TEXT NOT NULL,
+    checked_at INTEGER NOT NULL,
+    PRIMARY KEY (list_from, item_key)
+);
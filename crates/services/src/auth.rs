@@ -0,0 +1,360 @@
+use std::sync::Arc;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, TimeZone, Utc};
+use domain::AuthorSession;
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::SqlitePool;
+use tokio::sync::RwLock;
+
+/// How long an author stays logged in.
+pub const SESSION_DAYS: i64 = 30;
+
+/// Someone allowed to change the plan, with their password hashed by
+/// [`hash_password`].
+#[derive(Clone, Debug)]
+pub struct Author {
+    pub name: String,
+    password_hash: String,
+}
+
+impl Author {
+    pub fn new(name: impl Into<String>, password_hash: impl Into<String>) -> eyre::Result<Self> {
+        let name = name.into();
+        let password_hash = password_hash.into();
+
+        PasswordHash::new(&password_hash)
+            .map_err(|e| eyre::eyre!("invalid password hash for {name}: {e}"))?;
+
+        Ok(Self {
+            name,
+            password_hash,
+        })
+    }
+}
+
+/// Hashes a password with argon2, in the PHC string format expected by
+/// [`Author::new`].
+pub fn hash_password(password: &str) -> eyre::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| eyre::eyre!("could not hash password: {e}"))
+}
+
+fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// A session as it is stored. Only a hash of the token is kept, so the stored
+/// sessions can't be used to log in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredSession {
+    pub token_hash: String,
+    pub author: String,
+    pub csrf_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn put_session(&self, session: StoredSession) -> eyre::Result<()>;
+
+    async fn get_session(&self, token_hash: &str) -> eyre::Result<Option<StoredSession>>;
+
+    async fn delete_session(&self, token_hash: &str) -> eyre::Result<()>;
+}
+
+/// Sessions are lost on restart, used when no database is configured.
+#[derive(Clone, Default)]
+pub struct InMemorySessionRepository {
+    sessions: Arc<RwLock<Vec<StoredSession>>>,
+}
+
+#[async_trait]
+impl SessionRepository for InMemorySessionRepository {
+    async fn put_session(&self, session: StoredSession) -> eyre::Result<()> {
+        let mut sessions = self.sessions.write().await;
+        let now = Utc::now();
+        sessions.retain(|s| s.expires_at > now && s.token_hash != session.token_hash);
+        sessions.push(session);
+
+        Ok(())
+    }
+
+    async fn get_session(&self, token_hash: &str) -> eyre::Result<Option<StoredSession>> {
+        Ok(self
+            .sessions
+            .read()
+            .await
+            .iter()
+            .find(|s| s.token_hash == token_hash)
+            .cloned())
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> eyre::Result<()> {
+        self.sessions
+            .write()
+            .await
+            .retain(|s| s.token_hash != token_hash);
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteSessionRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteSessionRepository {
+    /// The schema is created by the migrations run in
+    /// [`crate::SqliteEventRepository::connect`].
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SessionRepository for SqliteSessionRepository {
+    async fn put_session(&self, session: StoredSession) -> eyre::Result<()> {
+        sqlx::query("DELETE FROM sessions WHERE expires_at <= ?")
+            .bind(Utc::now().timestamp())
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO sessions (token_hash, author, csrf_token, expires_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&session.token_hash)
+        .bind(&session.author)
+        .bind(&session.csrf_token)
+        .bind(session.expires_at.timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_session(&self, token_hash: &str) -> eyre::Result<Option<StoredSession>> {
+        let row: Option<(String, String, String, i64)> = sqlx::query_as(
+            "SELECT token_hash, author, csrf_token, expires_at FROM sessions WHERE token_hash = ?",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|(token_hash, author, csrf_token, expires_at)| {
+            Ok(StoredSession {
+                token_hash,
+                author,
+                csrf_token,
+                expires_at: Utc
+                    .timestamp_opt(expires_at, 0)
+                    .single()
+                    .ok_or_else(|| eyre::eyre!("invalid session expiry {expires_at}"))?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> eyre::Result<()> {
+        sqlx::query("DELETE FROM sessions WHERE token_hash = ?")
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Logs authors in and out, and looks up who is behind a session token.
+#[derive(Clone)]
+pub struct Auth {
+    authors: Arc<Vec<Author>>,
+    sessions: Arc<dyn SessionRepository>,
+    /// Checked when the author is unknown, so logging in takes as long
+    /// whether or not the name exists.
+    dummy_hash: Arc<String>,
+}
+
+impl Auth {
+    pub fn new(authors: Vec<Author>, sessions: Arc<dyn SessionRepository>) -> eyre::Result<Self> {
+        Ok(Self {
+            authors: Arc::new(authors),
+            sessions,
+            dummy_hash: Arc::new(hash_password("not a password")?),
+        })
+    }
+
+    /// Starts a session if the name and password match an author, returning
+    /// the token to hand to the browser.
+    pub async fn login(
+        &self,
+        name: &str,
+        password: &str,
+    ) -> eyre::Result<Option<(String, AuthorSession)>> {
+        let author = self
+            .authors
+            .iter()
+            .find(|a| a.name.eq_ignore_ascii_case(name.trim()));
+
+        let password_hash = author.map_or(self.dummy_hash.as_str(), |a| &a.password_hash);
+        let verified = verify_password(password, password_hash);
+        let Some(author) = author.filter(|_| verified) else {
+            tracing::info!(name = name, "failed login");
+            return Ok(None);
+        };
+
+        let token = random_token();
+        let session = StoredSession {
+            token_hash: hash_token(&token),
+            author: author.name.clone(),
+            csrf_token: random_token(),
+            expires_at: Utc::now() + chrono::Duration::days(SESSION_DAYS),
+        };
+        self.sessions.put_session(session.clone()).await?;
+
+        Ok(Some((
+            token,
+            AuthorSession {
+                name: session.author,
+                csrf_token: session.csrf_token,
+            },
+        )))
+    }
+
+    /// The author behind a session token, if the session is still valid and
+    /// the author still exists.
+    pub async fn session(&self, token: &str) -> eyre::Result<Option<AuthorSession>> {
+        let Some(session) = self.sessions.get_session(&hash_token(token)).await? else {
+            return Ok(None);
+        };
+
+        if session.expires_at <= Utc::now()
+            || !self.authors.iter().any(|a| a.name == session.author)
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(AuthorSession {
+            name: session.author,
+            csrf_token: session.csrf_token,
+        }))
+    }
+
+    pub async fn logout(&self, token: &str) -> eyre::Result<()> {
+        self.sessions.delete_session(&hash_token(token)).await
+    }
+}
+
+impl Default for Auth {
+    /// No authors, so nobody can log in.
+    fn default() -> Self {
+        Self {
+            authors: Default::default(),
+            sessions: Arc::new(InMemorySessionRepository::default()),
+            dummy_hash: Default::default(),
+        }
+    }
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Compares secrets in constant time.
+pub fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{InMemoryEventRepository, SqliteEventRepository};
+
+    use super::{
+        hash_password, tokens_match, Auth, Author, InMemorySessionRepository, SessionRepository,
+        SqliteSessionRepository,
+    };
+
+    async fn authors_can_log_in_and_out(sessions: Arc<dyn SessionRepository>) {
+        let author = Author::new("Kasper", hash_password("hunter2").unwrap()).unwrap();
+        let auth = Auth::new(vec![author], sessions).unwrap();
+
+        assert!(auth.login("Kasper", "wrong").await.unwrap().is_none());
+        assert!(auth.login("Nobody", "hunter2").await.unwrap().is_none());
+
+        let (token, session) = auth.login("kasper", "hunter2").await.unwrap().unwrap();
+        assert_eq!(session.name, "Kasper");
+        assert_eq!(auth.session(&token).await.unwrap(), Some(session.clone()));
+        assert!(auth.session("not-a-token").await.unwrap().is_none());
+
+        let (other_token, other_session) = auth.login("Kasper", "hunter2").await.unwrap().unwrap();
+        assert_ne!(token, other_token);
+        assert_ne!(session.csrf_token, other_session.csrf_token);
+
+        auth.logout(&token).await.unwrap();
+        assert!(auth.session(&token).await.unwrap().is_none());
+        assert!(auth.session(&other_token).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn in_memory_authors_can_log_in_and_out() {
+        authors_can_log_in_and_out(Arc::new(InMemorySessionRepository::default())).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_authors_can_log_in_and_out() {
+        let path = std::env::temp_dir().join(format!("bitebuds-{}.db", uuid::Uuid::new_v4()));
+        let events =
+            SqliteEventRepository::connect(&path, Arc::new(InMemoryEventRepository::default()))
+                .await
+                .unwrap();
+
+        authors_can_log_in_and_out(Arc::new(SqliteSessionRepository::new(
+            events.pool().clone(),
+        )))
+        .await;
+
+        events.pool().close().await;
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[test]
+    fn invalid_password_hashes_are_rejected() {
+        assert!(Author::new("Kasper", "hunter2").is_err());
+    }
+
+    #[test]
+    fn tokens_are_compared_exactly() {
+        assert!(tokens_match("abc", "abc"));
+        assert!(!tokens_match("abc", "abd"));
+        assert!(!tokens_match("abc", "abcd"));
+    }
+}
@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Where the content lives, and how to keep it up to date.
+#[derive(Clone, Debug)]
+pub struct ContentConfig {
+    /// Git repository to sync content from.
+    pub repo_url: Option<String>,
+    /// Branch to read content from, defaults to the default branch of the
+    /// repository.
+    pub branch: Option<String>,
+    /// Root of the content when it is read from the local filesystem.
+    pub path: PathBuf,
+    /// Directory of the events, relative to the root of the content.
+    pub events_dir: PathBuf,
+    /// Directory of the recipes, relative to the root of the content. Defaults
+    /// to `recipes` next to the events directory.
+    pub recipes_dir: Option<PathBuf>,
+    pub sync_interval: Duration,
+    /// Timezone of the household, used to decide what "today" is.
+    pub timezone: chrono_tz::Tz,
+}
+
+impl ContentConfig {
+    pub fn recipes_dir(&self) -> PathBuf {
+        match &self.recipes_dir {
+            Some(recipes_dir) => recipes_dir.clone(),
+            None => self.events_dir.with_file_name("recipes"),
+        }
+    }
+}
+
+impl Default for ContentConfig {
+    fn default() -> Self {
+        Self {
+            repo_url: None,
+            branch: None,
+            path: PathBuf::from("."),
+            events_dir: PathBuf::from("articles/events"),
+            recipes_dir: None,
+            sync_interval: Duration::from_secs(30),
+            timezone: chrono_tz::UTC,
+        }
+    }
+}
@@ -0,0 +1,622 @@
+//! Recipes written in [Cooklang](https://cooklang.org/docs/spec/), where the
+//! ingredients, cookware and timers are marked up in the steps themselves.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use domain::units::{split_quantity, Unit};
+use domain::{Ingredient, ParseDiagnostic};
+
+use crate::front_matter::{slug_from_path, split_front_matter};
+use crate::recipes::{RawIngredient, RawRecipe, RawReference};
+
+/// A mistake in a Cooklang recipe, on the given line of the file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub message: String,
+}
+
+pub(crate) fn is_cook_file(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "cook")
+}
+
+/// Reads every `.cook` file in `dir`. Like markdown recipes, files which can't
+/// be parsed are skipped and reported in `diagnostics`.
+pub(crate) async fn read_recipes(
+    dir: &Path,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> eyre::Result<Vec<RawRecipe>> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+
+    let mut recipes = vec![];
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let is_file = entry
+            .file_type()
+            .await
+            .map(|t| t.is_file())
+            .unwrap_or(false);
+        if !is_file || !is_cook_file(&entry.path()) {
+            continue;
+        }
+
+        match read_recipe(entry.path()).await {
+            Ok(recipe) => recipes.push(recipe),
+            Err(diagnostic) => {
+                tracing::warn!(
+                    path = diagnostic.path,
+                    line = diagnostic.line,
+                    message = diagnostic.message,
+                    "skipping recipe"
+                );
+                diagnostics.push(diagnostic)
+            }
+        }
+    }
+
+    Ok(recipes)
+}
+
+async fn read_recipe(path: PathBuf) -> Result<RawRecipe, ParseDiagnostic> {
+    let diagnostic = |message: String| ParseDiagnostic {
+        path: path.display().to_string(),
+        line: None,
+        column: None,
+        message,
+    };
+
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| diagnostic(e.to_string()))?;
+
+    parse(&slug_from_path(&path), &content).map_err(|e| ParseDiagnostic {
+        line: Some(e.line),
+        ..diagnostic(e.message)
+    })
+}
+
+/// Reads a Cooklang recipe. Metadata is given either as `>> key: value` lines
+/// or as yaml front matter, and the name is taken from `title`, or from the
+/// slug when there is none. Each paragraph is a step, with the markup replaced
+/// by the names of the ingredients and cookware and the length of the timers.
+pub fn parse(slug: &str, content: &str) -> Result<RawRecipe, SyntaxError> {
+    let mut recipe = PartialRecipe::default();
+
+    let (body, first_line) = match split_front_matter(content) {
+        Some((front_matter, body)) => {
+            let values: BTreeMap<String, serde_yaml::Value> = serde_yaml::from_str(front_matter)
+                .map_err(|e| SyntaxError {
+                    // The front matter starts on the line after the opening marker.
+                    line: e.location().map(|l| l.line() + 1).unwrap_or(1),
+                    message: e.to_string(),
+                })?;
+            for (key, value) in values {
+                let Some(value) = yaml_text(value) else {
+                    continue;
+                };
+                recipe
+                    .set_metadata(&key, &value)
+                    .map_err(|message| SyntaxError { line: 1, message })?;
+            }
+
+            (body, content[..content.len() - body.len()].lines().count())
+        }
+        None => (content, 0),
+    };
+
+    let mut in_comment = false;
+    for (index, line) in body.lines().enumerate() {
+        let line_number = first_line + index + 1;
+        let error = |message: String| SyntaxError {
+            line: line_number,
+            message,
+        };
+        let (line, commented) = strip_comments(line, &mut in_comment);
+        let line = line.trim();
+
+        if line.is_empty() && commented {
+            // Comments on their own lines don't end the step they are in.
+            continue;
+        } else if line.is_empty() {
+            recipe.end_step();
+        } else if let Some(metadata) = line.strip_prefix(">>") {
+            let (key, value) = metadata
+                .split_once(':')
+                .ok_or_else(|| error("metadata is written as `>> key: value`".into()))?;
+            recipe
+                .set_metadata(key.trim(), value.trim())
+                .map_err(error)?;
+        } else if let Some(note) = line.strip_prefix('>') {
+            recipe.add_note(note.trim());
+        } else if line.starts_with('=') {
+            // Sections only group the steps, which are numbered throughout.
+            recipe.end_step();
+        } else {
+            let text = recipe.read_step(line).map_err(error)?;
+            recipe.step.push(text);
+        }
+    }
+    recipe.end_step();
+
+    Ok(recipe.into_raw(slug))
+}
+
+/// A recipe while it is being read.
+#[derive(Default)]
+struct PartialRecipe {
+    id: Option<uuid::Uuid>,
+    name: Option<String>,
+    description: Option<String>,
+    servings: Option<u32>,
+    prep_time: Option<u32>,
+    cook_time: Option<u32>,
+    ingredients: Vec<Ingredient>,
+    cookware: Vec<String>,
+    steps: Vec<String>,
+    /// Lines of the step being read.
+    step: Vec<String>,
+    references: Vec<RawReference>,
+    metadata: HashMap<String, String>,
+}
+
+impl PartialRecipe {
+    /// Known keys are read into the recipe, written in any case and with or
+    /// without spaces, so `prep time`, `prep_time` and `prepTime` are the
+    /// same. Anything else is kept as metadata.
+    fn set_metadata(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let normalized = key.to_lowercase().replace(
+            |c: char| c.is_whitespace() || c == '_' || c == '-' || c == '.',
+            "",
+        );
+
+        match normalized.as_str() {
+            "title" | "name" => self.name = Some(value.to_string()),
+            "description" => self.description = Some(value.to_string()),
+            "id" => {
+                let id = value
+                    .parse()
+                    .map_err(|e| format!("`{key}` is not a valid id: {e}"))?;
+                self.id = Some(id);
+            }
+            "servings" | "serves" => {
+                let servings = split_quantity(value)
+                    .0
+                    .ok_or_else(|| format!("`{key}` must start with a number"))?;
+                self.servings = Some(servings.round() as u32);
+            }
+            "preptime" | "timeprep" => {
+                self.prep_time =
+                    Some(minutes(value).ok_or_else(|| format!("`{key}` must be a time"))?);
+            }
+            "cooktime" | "timecook" => {
+                self.cook_time =
+                    Some(minutes(value).ok_or_else(|| format!("`{key}` must be a time"))?);
+            }
+            "source" | "sourceurl" if value.starts_with("http") => {
+                self.references.push(RawReference::Url(value.to_string()));
+            }
+            _ => {
+                self.metadata.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_note(&mut self, note: &str) {
+        self.description = match self.description.take() {
+            Some(description) => Some(format!("{description} {note}")),
+            None => Some(note.to_string()),
+        };
+    }
+
+    fn end_step(&mut self) {
+        if !self.step.is_empty() {
+            self.steps.push(self.step.join(" "));
+            self.step.clear();
+        }
+    }
+
+    /// Collects the ingredients and cookware of a line of a step, and returns
+    /// the line as it reads without the markup.
+    fn read_step(&mut self, line: &str) -> Result<String, String> {
+        let mut text = String::new();
+        let mut rest = line;
+
+        while let Some(start) = rest.find(['@', '#', '~']) {
+            text.push_str(&rest[..start]);
+            let sigil = &rest[start..start + 1];
+            let after = &rest[start + 1..];
+
+            let component =
+                read_component(after)?.filter(|(name, _, _)| !name.is_empty() || sigil == "~");
+            let Some((name, amount, after)) = component else {
+                text.push_str(sigil);
+                rest = after;
+                continue;
+            };
+            rest = after;
+
+            match sigil {
+                "@" => {
+                    let (note, after) = read_note(rest)?;
+                    rest = after;
+                    text.push_str(name);
+                    self.add_ingredient(ingredient(name, amount, note));
+                }
+                "#" => {
+                    text.push_str(name);
+                    if !self.cookware.iter().any(|c| c == name) {
+                        self.cookware.push(name.to_string());
+                    }
+                }
+                _ => match amount {
+                    Some(amount) => text.push_str(&amount.replace('%', " ")),
+                    None => text.push_str(name),
+                },
+            }
+        }
+        text.push_str(rest);
+
+        Ok(text)
+    }
+
+    /// Ingredients are often mentioned in more than one step. Amounts of the
+    /// same ingredient in the same unit are added together.
+    fn add_ingredient(&mut self, ingredient: Ingredient) {
+        let existing = self.ingredients.iter_mut().find(|i| {
+            i.name.to_lowercase() == ingredient.name.to_lowercase() && i.unit == ingredient.unit
+        });
+
+        match existing {
+            Some(existing) => {
+                existing.quantity = match (existing.quantity, ingredient.quantity) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+                existing.note = existing.note.take().or(ingredient.note);
+            }
+            None => self.ingredients.push(ingredient),
+        }
+    }
+
+    fn into_raw(mut self, slug: &str) -> RawRecipe {
+        if !self.cookware.is_empty() {
+            self.metadata
+                .insert("cookware".into(), self.cookware.join(", "));
+        }
+
+        RawRecipe {
+            id: self.id,
+            name: self.name.unwrap_or_else(|| slug.to_string()),
+            description: self.description,
+            servings: self.servings,
+            prep_time: self.prep_time,
+            cook_time: self.cook_time,
+            ingredients: self
+                .ingredients
+                .into_iter()
+                .map(|i| RawIngredient::Detailed {
+                    name: i.name,
+                    quantity: i.quantity,
+                    unit: i.unit,
+                    note: i.note,
+                })
+                .collect(),
+            steps: self.steps,
+            references: self.references,
+            images: vec![],
+            metadata: (!self.metadata.is_empty()).then(|| self.metadata.into()),
+            slug: slug.to_string(),
+            content: String::new(),
+        }
+    }
+}
+
+/// The name and amount of an ingredient, cookware or timer, followed by the
+/// rest of the line.
+type Component<'a> = (&'a str, Option<&'a str>, &'a str);
+
+/// Reads the name and amount after `@`, `#` or `~`. Names of more than one
+/// word end with braces, as in `@olive oil{}`, while a single word can be
+/// written on its own. Returns `None` when there is no name or braces, such
+/// as for a `#` in the text.
+fn read_component(text: &str) -> Result<Option<Component<'_>>, String> {
+    let braces = text
+        .find(['{', '@', '#', '~'])
+        .filter(|&i| text[i..].starts_with('{'));
+
+    if let Some(open) = braces {
+        let close = text[open..]
+            .find('}')
+            .map(|i| open + i)
+            .ok_or_else(|| format!("`{{` after `{}` is never closed", text[..open].trim()))?;
+        let amount = text[open + 1..close].trim();
+
+        return Ok(Some((
+            text[..open].trim(),
+            (!amount.is_empty()).then_some(amount),
+            &text[close + 1..],
+        )));
+    }
+
+    let end = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+
+    Ok((end > 0).then(|| (&text[..end], None, &text[end..])))
+}
+
+/// A note on an ingredient, as in `@løg{1}(finthakket)`.
+fn read_note(text: &str) -> Result<(Option<&str>, &str), String> {
+    let Some(note) = text.strip_prefix('(') else {
+        return Ok((None, text));
+    };
+    let close = note
+        .find(')')
+        .ok_or_else(|| "`(` is never closed".to_string())?;
+
+    Ok((Some(note[..close].trim()), &note[close + 1..]))
+}
+
+/// An ingredient from its name and an amount such as `2`, `1/2%dl` or
+/// `=1%knsp`, where the `=` only tells Cooklang not to scale it. Amounts which
+/// aren't numbers, such as `{a little}`, are kept as a note.
+fn ingredient(name: &str, amount: Option<&str>, note: Option<&str>) -> Ingredient {
+    let (quantity, unit) = match amount.map(|a| a.split_once('%').unwrap_or((a, ""))) {
+        Some((quantity, unit)) => (quantity.trim().trim_start_matches('='), unit.trim()),
+        None => ("", ""),
+    };
+
+    let (quantity, note) = match split_quantity(quantity) {
+        (Some(quantity), "") => (Some(quantity), note.map(str::to_string)),
+        (_, "") => (None, note.map(str::to_string)),
+        (_, _) => (None, Some(quantity.to_string())),
+    };
+    let unit = (!unit.is_empty()).then(|| match Unit::parse(unit) {
+        Some(unit) => unit.symbol().to_string(),
+        None => unit.to_string(),
+    });
+
+    Ingredient {
+        name: name.to_string(),
+        quantity,
+        unit,
+        note,
+    }
+}
+
+/// Minutes in a time such as `20`, `20 minutes`, `1 hour` or `1h 30min`.
+/// Hours can also be written in Danish, as `timer`.
+fn minutes(text: &str) -> Option<u32> {
+    let mut total = None;
+    let mut rest = text;
+
+    while let (Some(amount), after) = split_quantity(rest) {
+        let end = after
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(after.len());
+        let unit = after[..end].to_lowercase();
+        let minutes = match unit.chars().next() {
+            Some('h' | 't') => amount * 60.0,
+            _ => amount,
+        };
+
+        total = Some(total.unwrap_or_default() + minutes);
+        rest = &after[end..];
+    }
+
+    total.map(|minutes: f64| minutes.round() as u32)
+}
+
+/// Removes `-- line comments` and `[- block comments -]` from a line, where
+/// `in_comment` says whether a block comment on an earlier line is still open.
+/// Also returns whether anything was removed.
+fn strip_comments(line: &str, in_comment: &mut bool) -> (String, bool) {
+    let mut stripped = String::with_capacity(line.len());
+    let mut commented = false;
+    let mut rest = line;
+
+    loop {
+        if *in_comment {
+            commented = true;
+            match rest.find("-]") {
+                Some(end) => {
+                    *in_comment = false;
+                    rest = &rest[end + 2..];
+                }
+                None => break,
+            }
+        }
+
+        match (rest.find("--"), rest.find("[-")) {
+            (Some(start), block) if block.is_none() || Some(start) < block => {
+                stripped.push_str(&rest[..start]);
+                return (stripped, true);
+            }
+            (_, Some(start)) => {
+                stripped.push_str(&rest[..start]);
+                *in_comment = true;
+                rest = &rest[start + 2..];
+            }
+            _ => {
+                stripped.push_str(rest);
+                break;
+            }
+        }
+    }
+
+    (stripped, commented)
+}
+
+fn yaml_text(value: serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(text) => Some(text),
+        serde_yaml::Value::Number(number) => Some(number.to_string()),
+        serde_yaml::Value::Bool(value) => Some(value.to_string()),
+        serde_yaml::Value::Sequence(values) => Some(
+            values
+                .into_iter()
+                .filter_map(yaml_text)
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use domain::{Event, Recipe};
+
+    use crate::recipes::{fetch_recipes, link_recipes, recipe_id_from_slug};
+    use crate::RawEvent;
+
+    use super::parse;
+
+    const BRUNSVIGER: &str = r#">> title: Brunsviger
+>> servings: 8
+>> prep time: 30 min
+>> cook time: 1 time
+>> source: https://example.com/brunsviger
+>> course: dessert
+
+-- Dejen skal hæve to gange
+Lun @mælk{2,5%dl} og rør @gær{50%g} ud i den.
+Tilsæt @hvedemel{500%g}, @sukker{2%spsk} og @salt{=1%knsp}.
+
+Ælt dejen i en #stor skål{} og lad den hæve i ~{45%minutes}.
+[- Mange bruger
+   mere smør -]
+Smelt @smør{200%g} og @brun farin{250%g}(gerne mørk) i en #gryde.
+
+= Bagning
+Hæld @smør{50%g} over og bag ved 200 grader i ~bagning{25%min}.
+"#;
+
+    #[test]
+    fn can_parse_cooklang_recipe() {
+        let recipe: Recipe = parse("brunsviger", BRUNSVIGER).unwrap().into();
+
+        assert_eq!(recipe.id, recipe_id_from_slug("brunsviger"));
+        assert_eq!(recipe.name, "Brunsviger");
+        assert_eq!(recipe.servings, Some(8));
+        assert_eq!(recipe.total_time_minutes(), Some(90));
+        assert_eq!(recipe.references[0].url, "https://example.com/brunsviger");
+
+        let metadata = recipe.metadata.unwrap();
+        assert_eq!(metadata.get("course"), Some("dessert"));
+        assert_eq!(metadata.get("cookware"), Some("stor skål, gryde"));
+
+        let names = recipe
+            .ingredients
+            .iter()
+            .map(|i| i.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "mælk",
+                "gær",
+                "hvedemel",
+                "sukker",
+                "salt",
+                "smør",
+                "brun farin"
+            ]
+        );
+        assert_eq!(recipe.ingredients[0].quantity, Some(2.5));
+        assert_eq!(recipe.ingredients[0].unit.as_deref(), Some("dl"));
+        assert_eq!(recipe.ingredients[4].unit.as_deref(), Some("knsp"));
+        assert_eq!(recipe.ingredients[5].quantity, Some(250.0));
+        assert_eq!(recipe.ingredients[6].note.as_deref(), Some("gerne mørk"));
+
+        assert_eq!(recipe.steps.len(), 3);
+        assert_eq!(
+            recipe.steps[0].text,
+            "Lun mælk og rør gær ud i den. Tilsæt hvedemel, sukker og salt."
+        );
+        assert_eq!(
+            recipe.steps[1].text,
+            "Ælt dejen i en stor skål og lad den hæve i 45 minutes. Smelt smør og brun farin i en gryde."
+        );
+        assert_eq!(
+            recipe.steps[2].text,
+            "Hæld smør over og bag ved 200 grader i 25 min."
+        );
+    }
+
+    #[test]
+    fn front_matter_and_loose_ingredients() {
+        let recipe: Recipe = parse(
+            "spejlæg",
+            "---\ntitle: Spejlæg\nservings: 2\ntags: [morgenmad, hurtig]\n---\nSteg @æg{4} med @salt og lidt @olie{en sjat}.\n",
+        )
+        .unwrap()
+        .into();
+
+        assert_eq!(recipe.name, "Spejlæg");
+        assert_eq!(recipe.servings, Some(2));
+        assert_eq!(
+            recipe.metadata.unwrap().get("tags"),
+            Some("morgenmad, hurtig")
+        );
+        assert_eq!(recipe.ingredients[0].quantity, Some(4.0));
+        assert_eq!(recipe.ingredients[1].name, "salt");
+        assert_eq!(recipe.ingredients[1].quantity, None);
+        assert_eq!(recipe.ingredients[2].note.as_deref(), Some("en sjat"));
+        assert_eq!(recipe.steps[0].text, "Steg æg med salt og lidt olie.");
+    }
+
+    #[test]
+    fn mistakes_are_reported_with_their_line() {
+        let error = parse("broken", ">> servings: 4\n\nRør @smør{100%g i.\n").unwrap_err();
+        assert_eq!(error.line, 3);
+
+        let error = parse("broken", ">> servings: mange\n").unwrap_err();
+        assert_eq!(error.line, 1);
+    }
+
+    #[tokio::test]
+    async fn events_link_to_cooklang_recipes_by_slug() {
+        let dir = std::env::temp_dir().join(format!("bitebuds-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(dir.join("brunsviger.cook"), BRUNSVIGER)
+            .await
+            .unwrap();
+        tokio::fs::write(dir.join("kringle.cook"), "Rør @smør{100%g i.\n")
+            .await
+            .unwrap();
+        tokio::fs::write(
+            dir.join("oksesteg.md"),
+            "---\nname: Gammeldags oksesteg\n---\n",
+        )
+        .await
+        .unwrap();
+
+        let (recipes, diagnostics) = fetch_recipes(dir.clone()).await.unwrap();
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+
+        assert_eq!(recipes.len(), 2);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].path.ends_with("kringle.cook"));
+        assert_eq!(diagnostics[0].line, Some(1));
+
+        let mut raw_event: RawEvent = serde_yaml::from_str(
+            r#"name: Fødselsdag
+time: 2023-04-14
+recipe: brunsviger"#,
+        )
+        .unwrap();
+        raw_event.slug = "2023-04-14-fodselsdag".into();
+        let event: Event = raw_event.into();
+
+        let mut events = vec![event];
+        link_recipes(&mut events, &recipes);
+
+        let brunsviger = recipes.iter().find(|r| r.slug == "brunsviger").unwrap();
+        assert_eq!(events[0].recipe_id, Some(brunsviger.id));
+    }
+}
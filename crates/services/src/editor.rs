@@ -0,0 +1,257 @@
+use domain::EventDraft;
+use serde_yaml::{Mapping, Value};
+
+use crate::front_matter::split_front_matter;
+use crate::RawEvent;
+
+pub const MAX_NAME_LENGTH: usize = 120;
+
+/// Name of the file of a new event, such as `2023-03-06-stegt-flaesk`, in the
+/// format `biteme generate article` uses.
+pub fn slug(date: chrono::NaiveDate, name: &str) -> String {
+    let mut name_slug = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        match c {
+            'æ' => name_slug.push_str("ae"),
+            'ø' => name_slug.push_str("oe"),
+            'å' => name_slug.push_str("aa"),
+            c if c.is_ascii_alphanumeric() => name_slug.push(c),
+            _ if !name_slug.ends_with('-') => name_slug.push('-'),
+            _ => {}
+        }
+    }
+
+    let name_slug = name_slug.trim_matches('-');
+    if name_slug.is_empty() {
+        return date.format("%Y-%m-%d").to_string();
+    }
+
+    format!("{}-{}", date.format("%Y-%m-%d"), name_slug)
+}
+
+/// Checks and tidies a draft before it is written.
+pub fn validate(mut draft: EventDraft) -> eyre::Result<EventDraft> {
+    let trimmed = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    draft.name = draft.name.trim().to_string();
+    if draft.name.is_empty() {
+        eyre::bail!("the meal needs a name");
+    }
+    if draft.name.chars().count() > MAX_NAME_LENGTH {
+        eyre::bail!("names can be at most {MAX_NAME_LENGTH} characters");
+    }
+
+    if draft.servings == Some(0) {
+        eyre::bail!("a meal is for at least one person");
+    }
+
+    draft.description = trimmed(draft.description);
+    draft.recipe = trimmed(draft.recipe);
+    draft.cover_image_url = trimmed(draft.cover_image_url);
+    draft.cover_image_alt = trimmed(draft.cover_image_alt);
+    if draft.cover_image_url.is_some() && draft.cover_image_alt.is_none() {
+        eyre::bail!("the cover image needs a description");
+    }
+
+    draft.tags = draft
+        .tags
+        .into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    draft.body = draft.body.trim().to_string();
+
+    Ok(draft)
+}
+
+/// Reads the editable parts of an event's markdown file.
+pub fn read_draft(contents: &str) -> eyre::Result<EventDraft> {
+    let (front_matter, body) =
+        split_front_matter(contents).ok_or_else(|| eyre::eyre!("missing front matter"))?;
+    let raw: RawEvent = serde_yaml::from_str(front_matter)?;
+
+    Ok(EventDraft {
+        name: raw.name,
+        description: raw.description,
+        date: raw.time.date,
+        start_time: raw.time.time_of_day,
+        meal: raw.meal,
+        servings: raw.servings,
+        tags: raw.tags,
+        recipe: raw.recipe,
+        cover_image_url: raw.cover_image.as_ref().map(|image| image.url.clone()),
+        cover_image_alt: raw.cover_image.map(|image| image.alt),
+        body: body.trim().to_string(),
+    })
+}
+
+/// Writes the draft into the front matter of `existing`, or of a new file.
+/// Keys the editor doesn't handle, such as `recurrence`, are kept as they
+/// are.
+pub fn render_file(existing: Option<&str>, draft: &EventDraft) -> eyre::Result<String> {
+    let mut front_matter = match existing.and_then(split_front_matter) {
+        Some((front_matter, _)) => {
+            serde_yaml::from_str::<Option<Mapping>>(front_matter)?.unwrap_or_default()
+        }
+        None => Mapping::new(),
+    };
+
+    let time = match draft.start_time {
+        Some(start_time) => format!(
+            "{} {}",
+            draft.date.format("%Y-%m-%d"),
+            start_time.format("%H:%M")
+        ),
+        None => draft.date.format("%Y-%m-%d").to_string(),
+    };
+
+    set(&mut front_matter, "name", Some(draft.name.clone().into()));
+    set(
+        &mut front_matter,
+        "description",
+        draft.description.clone().map(Value::from),
+    );
+    set(&mut front_matter, "time", Some(time.into()));
+    set(
+        &mut front_matter,
+        "meal",
+        draft.meal.map(serde_yaml::to_value).transpose()?,
+    );
+    set(
+        &mut front_matter,
+        "servings",
+        draft.servings.map(Value::from),
+    );
+    set(
+        &mut front_matter,
+        "tags",
+        (!draft.tags.is_empty()).then(|| draft.tags.clone().into()),
+    );
+    if draft.recipe.is_some() || front_matter.contains_key("recipe") {
+        set(
+            &mut front_matter,
+            "recipe",
+            draft.recipe.clone().map(Value::from),
+        );
+    }
+
+    let image_key = if front_matter.contains_key("cover_image") {
+        "cover_image"
+    } else {
+        "coverImage"
+    };
+    let cover_image = match (&draft.cover_image_url, &draft.cover_image_alt) {
+        (Some(url), Some(alt)) => {
+            let mut image = match front_matter.get(image_key) {
+                Some(Value::Mapping(image)) => image.clone(),
+                _ => Mapping::new(),
+            };
+            image.insert("url".into(), url.clone().into());
+            image.insert("alt".into(), alt.clone().into());
+            Some(Value::Mapping(image))
+        }
+        _ => None,
+    };
+    set(&mut front_matter, image_key, cover_image);
+
+    let front_matter = serde_yaml::to_string(&front_matter)?;
+    let contents = format!("---\n{front_matter}---\n\n{}\n", draft.body.trim());
+
+    // Make sure the file can be read back before it is written anywhere.
+    serde_yaml::from_str::<RawEvent>(&front_matter)?;
+
+    Ok(contents)
+}
+
+fn set(front_matter: &mut Mapping, key: &str, value: Option<Value>) {
+    match value {
+        Some(value) => {
+            front_matter.insert(key.into(), value);
+        }
+        None => {
+            front_matter.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, NaiveTime};
+    use domain::{EventDraft, MealSlot};
+
+    use super::{read_draft, render_file, slug, validate};
+
+    fn draft() -> EventDraft {
+        EventDraft {
+            name: "Stegt flæsk".into(),
+            description: Some("Med persillesovs".into()),
+            date: NaiveDate::from_ymd_opt(2023, 3, 6).unwrap(),
+            start_time: NaiveTime::from_hms_opt(18, 30, 0),
+            meal: Some(MealSlot::Dinner),
+            servings: Some(4),
+            tags: vec!["classic".into()],
+            recipe: None,
+            cover_image_url: None,
+            cover_image_alt: None,
+            body: "Kartofler fra haven.".into(),
+        }
+    }
+
+    #[test]
+    fn slugs_are_made_from_the_date_and_name() {
+        let date = NaiveDate::from_ymd_opt(2023, 3, 6).unwrap();
+
+        assert_eq!(slug(date, "Stegt flæsk"), "2023-03-06-stegt-flaesk");
+        assert_eq!(slug(date, " Pizza -- & salat! "), "2023-03-06-pizza-salat");
+        assert_eq!(slug(date, "!!"), "2023-03-06");
+    }
+
+    #[test]
+    fn new_files_can_be_read_back() {
+        let contents = render_file(None, &draft()).unwrap();
+
+        assert!(contents.starts_with("---\nname: Stegt flæsk\n"));
+        assert!(contents.contains("time: 2023-03-06 18:30\n"));
+        assert_eq!(read_draft(&contents).unwrap(), draft());
+    }
+
+    #[test]
+    fn unknown_front_matter_is_kept() {
+        let existing = "---\nid: 0b7d5d1e-8f59-4d51-a3d5-3c4bc1b4b0a3\nname: Old\ntime: 2023-03-01\nrecurrence:\n  rule: FREQ=WEEKLY\ncover_image:\n  url: old.jpg\n  alt: Old\n  metadata:\n    credit: Mormor\n---\n\nOld body\n";
+        let mut draft = draft();
+        draft.cover_image_url = Some("new.jpg".into());
+        draft.cover_image_alt = Some("New".into());
+
+        let contents = render_file(Some(existing), &draft).unwrap();
+
+        assert!(contents.contains("id: 0b7d5d1e-8f59-4d51-a3d5-3c4bc1b4b0a3\n"));
+        assert!(contents.contains("recurrence:\n  rule: FREQ=WEEKLY\n"));
+        assert!(contents.contains("credit: Mormor"));
+        assert!(!contents.contains("Old"));
+        assert_eq!(read_draft(&contents).unwrap(), draft);
+    }
+
+    #[test]
+    fn drafts_are_validated() {
+        let mut untidy = draft();
+        untidy.name = "  Stegt flæsk ".into();
+        untidy.description = Some(" ".into());
+        untidy.tags = vec![" classic ".into(), "".into()];
+        let tidy = validate(untidy).unwrap();
+        assert_eq!(tidy.name, "Stegt flæsk");
+        assert_eq!(tidy.description, None);
+        assert_eq!(tidy.tags, vec!["classic".to_string()]);
+
+        let mut nameless = draft();
+        nameless.name = " ".into();
+        assert!(validate(nameless).is_err());
+
+        let mut without_alt = draft();
+        without_alt.cover_image_url = Some("cover.jpg".into());
+        assert!(validate(without_alt).is_err());
+    }
+}
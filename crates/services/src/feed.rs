@@ -0,0 +1,227 @@
+use chrono::{DateTime, Utc};
+use domain::Event;
+
+/// How many of the most recently planned events are in the feeds.
+pub const FEED_LENGTH: usize = 50;
+
+/// An event together with when it was added to the plan.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedEntry {
+    pub event: Event,
+    pub added_at: DateTime<Utc>,
+}
+
+/// Serializes entries into an Atom feed. Links point to the event pages under
+/// `site_url`.
+pub fn atom(entries: &[FeedEntry], site_url: &str, now: DateTime<Utc>) -> String {
+    let site_url = site_url.trim_end_matches('/');
+    let updated = entries.iter().map(|e| e.added_at).max().unwrap_or(now);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str("  <title>Bitebuds</title>\n");
+    xml.push_str("  <subtitle>Newly planned meals</subtitle>\n");
+    xml.push_str(&format!("  <id>{}/feed.xml</id>\n", escape(site_url)));
+    xml.push_str(&format!(
+        "  <link rel=\"self\" href=\"{}/feed.xml\"/>\n",
+        escape(site_url)
+    ));
+    xml.push_str(&format!("  <link href=\"{}/\"/>\n", escape(site_url)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+
+    for entry in entries {
+        let event = &entry.event;
+        let url = event_url(site_url, event);
+
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape(&event.name)));
+        xml.push_str(&format!("    <id>urn:uuid:{}</id>\n", event.id));
+        xml.push_str(&format!("    <link href=\"{}\"/>\n", escape(&url)));
+        if let Some(image) = &event.cover_image {
+            xml.push_str(&format!(
+                "    <link rel=\"enclosure\" href=\"{}\" type=\"{}\"/>\n",
+                escape(&absolute_url(site_url, &image.url)),
+                image_type(&image.url)
+            ));
+        }
+        xml.push_str(&format!(
+            "    <published>{}</published>\n",
+            entry.added_at.to_rfc3339()
+        ));
+        xml.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            entry.added_at.to_rfc3339()
+        ));
+        if let Some(description) = &event.description {
+            xml.push_str(&format!("    <summary>{}</summary>\n", escape(description)));
+        }
+        xml.push_str(&format!(
+            "    <content type=\"html\">{}</content>\n",
+            escape(&content(event))
+        ));
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// Serializes entries into an RSS 2.0 feed. Links point to the event pages
+/// under `site_url`.
+pub fn rss(entries: &[FeedEntry], site_url: &str, now: DateTime<Utc>) -> String {
+    let site_url = site_url.trim_end_matches('/');
+    let updated = entries.iter().map(|e| e.added_at).max().unwrap_or(now);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str("  <channel>\n");
+    xml.push_str("    <title>Bitebuds</title>\n");
+    xml.push_str(&format!("    <link>{}/</link>\n", escape(site_url)));
+    xml.push_str("    <description>Newly planned meals</description>\n");
+    xml.push_str(&format!(
+        "    <atom:link rel=\"self\" type=\"application/rss+xml\" href=\"{}/rss.xml\"/>\n",
+        escape(site_url)
+    ));
+    xml.push_str(&format!(
+        "    <lastBuildDate>{}</lastBuildDate>\n",
+        updated.to_rfc2822()
+    ));
+
+    for entry in entries {
+        let event = &entry.event;
+        let url = event_url(site_url, event);
+
+        xml.push_str("    <item>\n");
+        xml.push_str(&format!("      <title>{}</title>\n", escape(&event.name)));
+        xml.push_str(&format!("      <link>{}</link>\n", escape(&url)));
+        xml.push_str(&format!(
+            "      <guid isPermaLink=\"false\">urn:uuid:{}</guid>\n",
+            event.id
+        ));
+        xml.push_str(&format!(
+            "      <pubDate>{}</pubDate>\n",
+            entry.added_at.to_rfc2822()
+        ));
+        if let Some(image) = &event.cover_image {
+            // The size of the image isn't known, which readers accept as 0.
+            xml.push_str(&format!(
+                "      <enclosure url=\"{}\" length=\"0\" type=\"{}\"/>\n",
+                escape(&absolute_url(site_url, &image.url)),
+                image_type(&image.url)
+            ));
+        }
+        xml.push_str(&format!(
+            "      <description>{}</description>\n",
+            escape(&content(event))
+        ));
+        xml.push_str("    </item>\n");
+    }
+
+    xml.push_str("  </channel>\n");
+    xml.push_str("</rss>\n");
+    xml
+}
+
+fn event_url(site_url: &str, event: &Event) -> String {
+    format!("{site_url}/events/{}", event.slug)
+}
+
+/// The html shown in feed readers: the date of the meal, followed by the
+/// rendered body, or the description when there is no body.
+fn content(event: &Event) -> String {
+    let mut when = event.time.format("%A %-d %B %Y").to_string();
+    if let Some(start_time) = event.start_time {
+        when.push_str(&format!(" at {}", start_time.format("%H:%M")));
+    }
+    if let Some(meal) = event.meal {
+        when = format!("{meal}, {when}");
+    }
+
+    let body = match (&event.body_html, &event.description) {
+        (Some(body_html), _) => body_html.clone(),
+        (None, Some(description)) => format!("<p>{}</p>", escape(description)),
+        (None, None) => String::new(),
+    };
+
+    format!("<p><strong>{}</strong></p>{body}", escape(&when))
+}
+
+fn absolute_url(site_url: &str, url: &str) -> String {
+    if url.starts_with('/') {
+        format!("{site_url}{url}")
+    } else {
+        url.to_string()
+    }
+}
+
+fn image_type(url: &str) -> &'static str {
+    let extension = url
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        _ => "image/jpeg",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+    use domain::Event;
+
+    use crate::RawEvent;
+
+    use super::{atom, rss, FeedEntry};
+
+    fn entry(front_matter: &str, added_at: chrono::DateTime<Utc>) -> FeedEntry {
+        let mut raw_event: RawEvent = serde_yaml::from_str(front_matter).unwrap();
+        raw_event.slug = "2023-03-06-gammeldags-oksesteg".into();
+        raw_event.content = "Med *brun* sovs & kartofler".into();
+        let event: Event = raw_event.into();
+
+        FeedEntry { event, added_at }
+    }
+
+    #[test]
+    fn feeds_have_an_entry_per_event_with_its_cover_image() {
+        let now = Utc.with_ymd_and_hms(2023, 3, 1, 12, 0, 0).unwrap();
+        let entries = vec![entry(
+            "name: Steg & sovs\ntime: 2023-03-06 18:30\nmeal: dinner\ncoverImage:\n  url: /assets/steg.png\n  alt: Steg",
+            now,
+        )];
+
+        let atom = atom(&entries, "https://bitebuds.example/", now);
+        assert!(atom.contains("<title>Steg &amp; sovs</title>"));
+        assert!(atom.contains(
+            "<link href=\"https://bitebuds.example/events/2023-03-06-gammeldags-oksesteg\"/>"
+        ));
+        assert!(atom.contains(
+            "<link rel=\"enclosure\" href=\"https://bitebuds.example/assets/steg.png\" type=\"image/png\"/>"
+        ));
+        assert!(atom.contains("<published>2023-03-01T12:00:00+00:00</published>"));
+        assert!(atom.contains("Dinner, Monday 6 March 2023 at 18:30"));
+        assert!(atom.contains("&lt;em&gt;brun&lt;/em&gt;"));
+
+        let rss = rss(&entries, "https://bitebuds.example", now);
+        assert!(rss.contains("<pubDate>Wed, 1 Mar 2023 12:00:00 +0000</pubDate>"));
+        assert!(rss.contains(
+            "<enclosure url=\"https://bitebuds.example/assets/steg.png\" length=\"0\" type=\"image/png\"/>"
+        ));
+        assert!(rss.contains(&format!("urn:uuid:{}", entries[0].event.id)));
+    }
+}
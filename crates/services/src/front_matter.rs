@@ -0,0 +1,161 @@
+use domain::ParseDiagnostic;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+
+/// A markdown file from the content repository, split into its parsed front
+/// matter and the body after it.
+pub(crate) struct Document<T> {
+    pub slug: String,
+    pub front_matter: T,
+    pub body: String,
+}
+
+/// Reads every document in `dir`. Files which can't be parsed are skipped and
+/// reported in `diagnostics`, so one broken file doesn't hide the rest.
+pub(crate) async fn read_documents<T: DeserializeOwned>(
+    dir: &Path,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> eyre::Result<Vec<Document<T>>> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+
+    let mut documents = vec![];
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        match read_document(entry.path()).await {
+            Ok(Some(document)) => documents.push(document),
+            Ok(None) => {}
+            Err(diagnostic) => {
+                tracing::warn!(
+                    path = diagnostic.path,
+                    line = diagnostic.line,
+                    message = diagnostic.message,
+                    "skipping document"
+                );
+                diagnostics.push(diagnostic)
+            }
+        }
+    }
+
+    Ok(documents)
+}
+
+pub(crate) async fn read_document<T: DeserializeOwned>(
+    path: PathBuf,
+) -> Result<Option<Document<T>>, ParseDiagnostic> {
+    let diagnostic = |message: String| ParseDiagnostic {
+        path: path.display().to_string(),
+        line: None,
+        column: None,
+        message,
+    };
+
+    let metadata = tokio::fs::metadata(&path)
+        .await
+        .map_err(|e| diagnostic(e.to_string()))?;
+    // Cooklang recipes may also start with front matter, but are read by
+    // `crate::cooklang`.
+    if !metadata.is_file() || crate::cooklang::is_cook_file(&path) {
+        return Ok(None);
+    }
+
+    let file = tokio::fs::read(&path)
+        .await
+        .map_err(|e| diagnostic(e.to_string()))?;
+    let content = std::str::from_utf8(&file).map_err(|e| diagnostic(e.to_string()))?;
+
+    let Some((raw_front_matter, body)) = split_front_matter(content) else {
+        if path.extension().map(|e| e == "md").unwrap_or(false) {
+            return Err(diagnostic("missing front matter".into()));
+        }
+        return Ok(None);
+    };
+
+    let front_matter = serde_yaml::from_str(raw_front_matter).map_err(|e| {
+        let location = e.location();
+        ParseDiagnostic {
+            // The front matter starts on the line after the opening marker.
+            line: location.as_ref().map(|l| l.line() + 1),
+            column: location.as_ref().map(|l| l.column()),
+            ..diagnostic(e.to_string())
+        }
+    })?;
+
+    Ok(Some(Document {
+        slug: slug_from_path(&path),
+        front_matter,
+        body: body.to_string(),
+    }))
+}
+
+/// Splits a markdown file into its yaml front matter and the body after it.
+pub(crate) fn split_front_matter(content: &str) -> Option<(&str, &str)> {
+    let after_marker = content.strip_prefix("---\n")?;
+    let marker_end = after_marker.find("---\n")?;
+
+    Some((&after_marker[..marker_end], &after_marker[marker_end + 4..]))
+}
+
+pub(crate) fn slug_from_path(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Diagnostics are shown to authors, who know their files relative to the root
+/// of the content repository rather than wherever it happens to be checked out.
+pub(crate) fn relative_to(diagnostics: &mut [ParseDiagnostic], root: &Path) {
+    for diagnostic in diagnostics {
+        if let Ok(path) = Path::new(&diagnostic.path).strip_prefix(root) {
+            diagnostic.path = path.display().to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use domain::ParseDiagnostic;
+
+    use crate::{fetch_events, RawEvent};
+
+    use super::read_documents;
+
+    #[tokio::test]
+    async fn broken_documents_are_reported_and_skipped() {
+        let dir = std::env::temp_dir().join(format!("bitebuds-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(
+            dir.join("2023-03-06-oksesteg.md"),
+            "---\nname: Oksesteg\ntime: 2023-03-06\n---\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            dir.join("2023-03-07-broken.md"),
+            "---\nname: Broken\ntime: [2023\n---\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(dir.join("notes.md"), "no front matter here")
+            .await
+            .unwrap();
+
+        let mut diagnostics: Vec<ParseDiagnostic> = vec![];
+        let documents = read_documents::<RawEvent>(&dir, &mut diagnostics)
+            .await
+            .unwrap();
+        let (events, _) = fetch_events(dir.clone()).await.unwrap();
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+
+        assert_eq!(documents.len(), 1);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].slug, "2023-03-06-oksesteg");
+
+        diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].path.ends_with("2023-03-07-broken.md"));
+        assert_eq!(diagnostics[0].line, Some(3));
+        assert!(diagnostics[1].path.ends_with("notes.md"));
+        assert_eq!(diagnostics[1].message, "missing front matter");
+    }
+}
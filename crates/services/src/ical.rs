@@ -0,0 +1,233 @@
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use domain::Event;
+
+/// How long a meal with a time of day but no duration is shown as lasting.
+const DEFAULT_DURATION_MINUTES: i64 = 60;
+
+/// Lines longer than this many octets are folded, see RFC 5545 section 3.1.
+const MAX_LINE_OCTETS: usize = 75;
+
+const DATE_FORMAT: &str = "%Y%m%d";
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Serializes events into an RFC 5545 calendar which can be subscribed to.
+///
+/// Events with a time of day are converted from `timezone` to UTC, so the
+/// calendar doesn't need to carry timezone definitions. Events without one are
+/// all-day events. Links point to the event pages under `site_url`.
+pub fn calendar(
+    events: &[Event],
+    timezone: chrono_tz::Tz,
+    site_url: &str,
+    now: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Bitebuds//Meal plan//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Bitebuds".to_string(),
+        "REFRESH-INTERVAL;VALUE=DURATION:PT1H".to_string(),
+        "X-PUBLISHED-TTL:PT1H".to_string(),
+    ];
+
+    for event in events {
+        lines.extend(event_lines(event, timezone, site_url, now));
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .into_iter()
+        .map(|line| fold(&line))
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+fn event_lines(
+    event: &Event,
+    timezone: chrono_tz::Tz,
+    site_url: &str,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let url = format!("{}/events/{}", site_url.trim_end_matches('/'), event.slug);
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        // Ids are stable across reloads, and occurrences of recurring events
+        // have ids of their own.
+        format!("UID:{}@bitebuds", event.id),
+        format!("DTSTAMP:{}", now.format(DATE_TIME_FORMAT)),
+    ];
+
+    match event.start_time {
+        Some(start_time) => {
+            let start = to_utc(event.time.and_time(start_time), timezone);
+            let minutes = event
+                .duration_minutes
+                .map(i64::from)
+                .unwrap_or(DEFAULT_DURATION_MINUTES);
+            // Durations running past the end of the calendar are typos.
+            let end = start
+                .checked_add_signed(Duration::minutes(minutes))
+                .or_else(|| start.checked_add_signed(Duration::minutes(DEFAULT_DURATION_MINUTES)))
+                .unwrap_or(start);
+
+            lines.push(format!("DTSTART:{}", start.format(DATE_TIME_FORMAT)));
+            lines.push(format!("DTEND:{}", end.format(DATE_TIME_FORMAT)));
+        }
+        None => {
+            let end = event.time.succ_opt().unwrap_or(event.time);
+
+            lines.push(format!(
+                "DTSTART;VALUE=DATE:{}",
+                event.time.format(DATE_FORMAT)
+            ));
+            lines.push(format!("DTEND;VALUE=DATE:{}", end.format(DATE_FORMAT)));
+            lines.push("TRANSP:TRANSPARENT".to_string());
+        }
+    }
+
+    lines.push(format!("SUMMARY:{}", escape(&event.name)));
+
+    // Not every client shows the url property, so it is repeated in the
+    // description.
+    let description = match &event.description {
+        Some(description) => format!("{description}\n\n{url}"),
+        None => url.clone(),
+    };
+    lines.push(format!("DESCRIPTION:{}", escape(&description)));
+    lines.push(format!("URL:{url}"));
+
+    if !event.tags.is_empty() {
+        let categories = event
+            .tags
+            .iter()
+            .map(|tag| escape(tag))
+            .collect::<Vec<_>>()
+            .join(",");
+        lines.push(format!("CATEGORIES:{categories}"));
+    }
+
+    lines.push("END:VEVENT".to_string());
+
+    lines
+}
+
+/// Local times skipped by a daylight saving change are moved an hour ahead,
+/// like a clock would be.
+fn to_utc(local: NaiveDateTime, timezone: chrono_tz::Tz) -> DateTime<Utc> {
+    timezone
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            let local = local.checked_add_signed(Duration::hours(1))?;
+            timezone.from_local_datetime(&local).earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Ends the line with CRLF, folding it so no line is longer than 75 octets.
+/// Continuation lines start with a space, which counts towards the limit.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut octets = 0;
+
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+
+    folded
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use domain::Event;
+
+    use crate::RawEvent;
+
+    use super::calendar;
+
+    fn event(front_matter: &str) -> Event {
+        let mut raw_event: RawEvent = serde_yaml::from_str(front_matter).unwrap();
+        raw_event.slug = "2023-03-06-gammeldags-oksesteg".into();
+        raw_event.into()
+    }
+
+    #[test]
+    fn events_are_all_day_unless_they_have_a_time_of_day() {
+        let now = Utc.with_ymd_and_hms(2023, 3, 1, 12, 0, 0).unwrap();
+        let events = vec![
+            event("name: Oksesteg\ntime: 2023-03-06"),
+            event("name: Oksesteg\ntime: 2023-03-06 18:30\nduration: 90\ntags: [sunday, guests]"),
+        ];
+
+        let ics = calendar(
+            &events,
+            chrono_tz::Europe::Copenhagen,
+            "https://bitebuds.example/",
+            now,
+        );
+        let lines = ics.split("\r\n").collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20230306"));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20230307"));
+        assert!(lines.contains(&"DTSTART:20230306T173000Z"));
+        assert!(lines.contains(&"DTEND:20230306T190000Z"));
+        assert!(lines.contains(&"DTSTAMP:20230301T120000Z"));
+        assert!(lines.contains(&"CATEGORIES:sunday,guests"));
+        assert!(
+            lines.contains(&"URL:https://bitebuds.example/events/2023-03-06-gammeldags-oksesteg")
+        );
+        assert!(lines.contains(&format!("UID:{}@bitebuds", events[0].id).as_str()));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn durations_past_the_end_of_the_calendar_fall_back_to_the_default() {
+        let now = Utc.with_ymd_and_hms(2023, 3, 1, 12, 0, 0).unwrap();
+        let mut late = event("name: Oksesteg\ntime: 2023-03-06 18:30");
+        late.time = NaiveDate::from_ymd_opt(262_142, 12, 31).unwrap();
+        late.duration_minutes = Some(u32::MAX);
+
+        let ics = calendar(&[late], chrono_tz::UTC, "https://bitebuds.example", now);
+
+        assert!(ics.contains("T183000Z\r\n"));
+        assert!(ics.contains("T193000Z\r\n"));
+    }
+
+    #[test]
+    fn text_is_escaped_and_long_lines_are_folded() {
+        let now = Utc.with_ymd_and_hms(2023, 3, 1, 12, 0, 0).unwrap();
+        let events = vec![event(
+            "name: Steg, kartofler; sovs\ntime: 2023-03-06\ndescription: Æbleflæsk med løg og rugbrød, som mormor lavede det hver søndag",
+        )];
+
+        let ics = calendar(&events, chrono_tz::UTC, "https://bitebuds.example", now);
+
+        assert!(ics.contains("SUMMARY:Steg\\, kartofler\\; sovs\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert!(ics.contains("\r\n "));
+        assert!(ics
+            .replace("\r\n ", "")
+            .contains("som mormor lavede det hver søndag\\n\\nhttps://bitebuds.example/events/"));
+    }
+}
@@ -1,10 +1,44 @@
 use cached::proc_macro::once;
-use domain::{Event, Image, Metadata};
-use gitevents_sdk::events::EventResponse;
+use domain::{
+    Attendance, Attendees, EditableEvent, Event, EventDraft, EventPage, Image, MealSlot, Metadata,
+    ParseDiagnostic, Recipe, Rsvp, ShoppingList, SortOrder, SyncStatus,
+};
+use front_matter::{read_documents, Document};
+use markdown::render_markdown;
+use recipes::recipe_id_from_slug;
+use recurrence::RawRecurrence;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+pub use auth::{
+    Auth, Author, InMemorySessionRepository, SessionRepository, SqliteSessionRepository,
+};
+pub use config::ContentConfig;
+pub use repository::{
+    EventFileChange, EventRepository, FileSystemEventRepository, GitEventRepository,
+    InMemoryEventRepository, SqliteEventRepository,
+};
+pub use rsvp::{InMemoryRsvpRepository, RsvpRepository, SqliteRsvpRepository};
+pub use shopping::{
+    InMemoryShoppingListRepository, ShoppingListRepository, SqliteShoppingListRepository,
+};
+
+pub mod auth;
+mod config;
+pub mod cooklang;
+pub mod editor;
+pub mod feed;
+mod front_matter;
+pub mod ical;
+pub mod markdown;
+pub mod pagination;
+pub mod recipes;
+pub mod recurrence;
+pub mod repository;
+pub mod rsvp;
+pub mod shopping;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RawImage {
     pub url: String,
@@ -14,207 +48,688 @@ pub struct RawImage {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RawEvent {
+    pub id: Option<uuid::Uuid>,
     #[serde(alias = "coverImage")]
     pub cover_image: Option<RawImage>,
     pub name: String,
     pub description: Option<String>,
     #[serde(with = "short_time_stamp")]
-    pub time: chrono::NaiveDate,
+    pub time: RawTime,
+    /// Minutes the meal is expected to last.
+    pub duration: Option<u32>,
+    pub meal: Option<MealSlot>,
+    /// How many people the meal is for.
+    pub servings: Option<u32>,
+    pub recurrence: Option<RawRecurrence>,
+    #[serde(default)]
+    pub tags: Vec<String>,
     pub recipe_id: Option<uuid::Uuid>,
+    /// Slug of the recipe, an alternative to `recipe_id`.
+    pub recipe: Option<String>,
     //pub images: Vec<RawImage>,
     pub metadata: Option<Metadata>,
     #[serde(skip)]
+    pub slug: String,
+    #[serde(skip)]
     pub content: String,
 }
 
+/// The date of an event, optionally followed by the time of day it is served,
+/// such as `2023-03-06` or `2023-03-06 18:30`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawTime {
+    pub date: chrono::NaiveDate,
+    pub time_of_day: Option<chrono::NaiveTime>,
+}
+
+/// Namespace used to derive event ids from their slug, so that an event keeps
+/// its id across reloads of the content repository.
+const EVENT_NAMESPACE: uuid::Uuid =
+    uuid::Uuid::from_u128(0x6b1e_2f0c_5d3a_4c1e_9a57_0f2d_8e4b_13c6);
+
+pub fn event_id_from_slug(slug: &str) -> uuid::Uuid {
+    uuid::Uuid::new_v5(&EVENT_NAMESPACE, slug.as_bytes())
+}
+
 mod short_time_stamp {
-    use chrono::NaiveDate;
+    use chrono::{NaiveDate, NaiveDateTime};
     use serde::{self, Deserialize, Deserializer, Serializer};
 
-    const FORMAT: &'static str = "%Y-%m-%d";
+    use super::RawTime;
 
-    pub fn serialize<S>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error>
+    const FORMAT: &'static str = "%Y-%m-%d";
+    const TIME_FORMAT: &str = "%H:%M";
+    const DATE_TIME_FORMATS: [&str; 4] = [
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+    ];
+
+    pub fn serialize<S>(time: &RawTime, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let s = format!("{}", date.format(FORMAT));
+        let s = match time.time_of_day {
+            Some(time_of_day) => format!(
+                "{} {}",
+                time.date.format(FORMAT),
+                time_of_day.format(TIME_FORMAT)
+            ),
+            None => format!("{}", time.date.format(FORMAT)),
+        };
         serializer.serialize_str(&s)
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<RawTime, D::Error>
     where
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        NaiveDate::parse_from_str(&s, FORMAT).map_err(serde::de::Error::custom)
+        if let Ok(date) = NaiveDate::parse_from_str(&s, FORMAT) {
+            return Ok(RawTime {
+                date,
+                time_of_day: None,
+            });
+        }
+
+        DATE_TIME_FORMATS
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(&s, format).ok())
+            .map(|date_time| RawTime {
+                date: date_time.date(),
+                time_of_day: Some(date_time.time()),
+            })
+            .ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "invalid time {s}, expected a date such as 2023-03-06 or 2023-03-06 18:30"
+                ))
+            })
     }
 }
 
 impl From<RawEvent> for Event {
     fn from(value: RawEvent) -> Self {
+        let id = value.id.unwrap_or_else(|| event_id_from_slug(&value.slug));
+
         Self {
-            id: uuid::Uuid::new_v4(),
-            cover_image: value.cover_image.map(|ci| ci.into()),
+            id,
+            slug: value.slug,
+            cover_image: value.cover_image.map(|ci| ci.into_image(id)),
             name: value.name,
             description: value.description,
-            time: value.time,
-            recipe_id: value.recipe_id,
+            time: value.time.date,
+            start_time: value.time.time_of_day,
+            duration_minutes: value.duration,
+            meal: value.meal,
+            servings: value.servings,
+            recurrence: value.recurrence.map(|r| r.into()),
+            series_id: None,
+            tags: value.tags,
+            recipe_id: value
+                .recipe_id
+                .or_else(|| value.recipe.as_deref().map(recipe_id_from_slug)),
             images: vec![],
             metadata: value.metadata,
+            body_html: render_markdown(&value.content),
         }
     }
 }
 
-impl From<RawImage> for Image {
-    fn from(value: RawImage) -> Self {
-        Self {
-            id: uuid::Uuid::new_v4(),
-            url: value.url,
-            alt: value.alt,
-            metadata: value.metadata,
+impl RawImage {
+    /// Images don't carry an id of their own, so it is derived from the owning
+    /// event and the image url.
+    pub fn into_image(self, owner_id: uuid::Uuid) -> Image {
+        Image {
+            id: uuid::Uuid::new_v5(&owner_id, self.url.as_bytes()),
+            url: self.url,
+            alt: self.alt,
+            metadata: self.metadata,
         }
     }
 }
 
-struct InnerEventStore {
-    url: Option<String>,
-    pub path: PathBuf,
-    events: Arc<tokio::sync::RwLock<Vec<Event>>>,
-    url_path: Option<String>,
+/// How far ahead recurring events are expanded when listing upcoming events.
+pub const RECURRENCE_WINDOW_WEEKS: i64 = 12;
+
+/// How far from today occurrences of recurring events are looked up by id.
+const OCCURRENCE_LOOKUP_DAYS: i64 = 366;
+
+/// Longest date range events can be queried for, as recurring events are
+/// expanded for every day of it.
+pub const MAX_RANGE_DAYS: i64 = 2 * 366;
+
+/// Checks a date range events are queried for, see [`MAX_RANGE_DAYS`].
+pub fn check_range(from: chrono::NaiveDate, until: chrono::NaiveDate) -> Result<(), String> {
+    if until < from {
+        return Err("until is before from".into());
+    }
+    if until - from > chrono::Duration::days(MAX_RANGE_DAYS) {
+        return Err(format!("the range can be at most {MAX_RANGE_DAYS} days"));
+    }
+
+    Ok(())
 }
 
+/// Base commits come from the editor form, so only full commit ids are passed
+/// on to git.
+fn check_base_commit(base_commit: Option<&str>) -> eyre::Result<()> {
+    match base_commit {
+        Some(commit)
+            if commit.len() != 40
+                || !commit
+                    .bytes()
+                    .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) =>
+        {
+            eyre::bail!("the editor was opened from an unknown version, reload and try again")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Entry point for the web layer. It picks a repository from the config, and
+/// answers queries which don't depend on where the content is stored.
 #[derive(Clone)]
 pub struct EventStore {
-    inner: Arc<InnerEventStore>,
+    repository: Arc<dyn EventRepository>,
+    rsvps: Arc<dyn RsvpRepository>,
+    sessions: Arc<dyn SessionRepository>,
+    shopping: Arc<dyn ShoppingListRepository>,
+    timezone: chrono_tz::Tz,
 }
 
 impl EventStore {
-    pub fn new(path: PathBuf) -> Self {
-        let article_repo_url = std::env::var("BITE_ARTICLE_REPO_URL")
-            .map(|a| (a != "").then(|| a))
-            .unwrap_or(None);
-        let article_repo_path = std::env::var("BITE_ARTICLE_REPO_PATH")
-            .map(|a| (a != "").then(|| a))
-            .unwrap_or(None);
+    pub fn new(config: ContentConfig) -> Self {
+        let timezone = config.timezone;
+
+        Self::with_repository(content_repository(config), timezone)
+    }
+
+    /// Like [`EventStore::new`], but the content is mirrored into a SQLite
+    /// database at `path` and served from there. Answers from guests, sessions
+    /// of authors and shopping lists are kept in the same database.
+    pub async fn with_database(config: ContentConfig, path: &Path) -> eyre::Result<Self> {
+        let timezone = config.timezone;
+        let repository = SqliteEventRepository::connect(path, content_repository(config)).await?;
+        let rsvps = SqliteRsvpRepository::new(repository.pool().clone());
+        let sessions = SqliteSessionRepository::new(repository.pool().clone());
+        let shopping = SqliteShoppingListRepository::new(repository.pool().clone());
+
+        Ok(Self {
+            repository: Arc::new(repository),
+            rsvps: Arc::new(rsvps),
+            sessions: Arc::new(sessions),
+            shopping: Arc::new(shopping),
+            timezone,
+        })
+    }
+
+    /// Answers from guests, sessions of authors and shopping lists are kept in
+    /// memory only.
+    pub fn with_repository(repository: Arc<dyn EventRepository>, timezone: chrono_tz::Tz) -> Self {
         Self {
-            inner: Arc::new(InnerEventStore {
-                url: article_repo_url,
-                url_path: article_repo_path,
-                path,
-                events: Default::default(),
-            }),
+            repository,
+            rsvps: Arc::new(InMemoryRsvpRepository::default()),
+            sessions: Arc::new(InMemorySessionRepository::default()),
+            shopping: Arc::new(InMemoryShoppingListRepository::default()),
+            timezone,
         }
     }
 
     pub async fn bootstrap(&self) -> eyre::Result<()> {
         tracing::info!("boostrapping event_store");
-        //let mut event_path = self.inner.path.clone();
-        //event_path.push("events");
-
-        //let events = fetch_events(event_path.clone()).await?;
-
-        //let mut e = self.inner.events.write().await;
-        //*e = events;
-
-        if let Some(repo_url) = self.inner.url.clone() {
-            tracing::info!(repo_url = repo_url, "subscribing to repo");
-            let inner = self.inner.clone();
-
-            tokio::task::spawn(async move {
-                gitevents_sdk::builder::Builder::new()
-                    .set_generic_git_url(repo_url)
-                    .set_scheduler_opts(&gitevents_sdk::cron::SchedulerOpts {
-                        duration: std::time::Duration::from_secs(30),
-                    })
-                    .action(move |req| {
-                        let inner = inner.clone();
-
-                        async move {
-                            tracing::info!("updating articles");
-                            let mut event_path = req.git.path.clone();
-                            event_path.push(inner.url_path.as_ref().unwrap());
-
-                            tracing::debug!(
-                                path = event_path.display().to_string(),
-                                "reading from"
-                            );
-
-                            let events = fetch_events(event_path).await.unwrap();
-
-                            let mut e = inner.events.write().await;
-                            *e = events.clone();
-
-                            Ok(EventResponse {})
-                        }
-                    })
-                    .execute()
-                    .await
-                    .unwrap();
-            });
-        }
 
-        Ok(())
+        self.repository.bootstrap().await
+    }
+
+    pub fn timezone(&self) -> chrono_tz::Tz {
+        self.timezone
     }
 
+    /// Where sessions of authors are kept, stored alongside the answers from
+    /// guests.
+    pub fn sessions(&self) -> Arc<dyn SessionRepository> {
+        self.sessions.clone()
+    }
+
+    /// The current date in the timezone of the household.
+    pub fn today(&self) -> chrono::NaiveDate {
+        chrono::Utc::now()
+            .with_timezone(&self.timezone)
+            .date_naive()
+    }
+
+    /// Events from today on. Recurring events are expanded into their
+    /// occurrences for the coming weeks.
     pub async fn get_upcoming_events(&self) -> eyre::Result<Vec<Event>> {
-        let events = self.inner.events.read().await.clone();
+        let today = self.today();
+        let until = today + chrono::Duration::weeks(RECURRENCE_WINDOW_WEEKS);
+
+        let (recurring, single): (Vec<Event>, Vec<Event>) = self
+            .repository
+            .list_events()
+            .await?
+            .into_iter()
+            .partition(|e| e.recurrence.is_some());
+
+        let mut events: Vec<Event> = single.into_iter().filter(|e| e.time >= today).collect();
+        events.extend(recurrence::expand(recurring, today, until));
 
         Ok(events)
     }
 
+    /// Events between `from` and `until`, both included, with recurring
+    /// events expanded, in the order they happen.
+    pub async fn get_events_between(
+        &self,
+        from: chrono::NaiveDate,
+        until: chrono::NaiveDate,
+    ) -> eyre::Result<Vec<Event>> {
+        let events = self.repository.list_events_between(from, until).await?;
+
+        let mut events = recurrence::expand(events, from, until);
+        events.sort_by_key(|e| e.sort_key());
+
+        Ok(events)
+    }
+
+    /// The most recently planned events, newest first, for feeds. Recurring
+    /// events are a single entry. Events the repository hasn't tracked are
+    /// counted as added on their date.
+    pub async fn get_recently_added_events(&self) -> eyre::Result<Vec<feed::FeedEntry>> {
+        let first_seen = self.repository.list_first_seen().await?;
+
+        let mut entries = self
+            .repository
+            .list_events()
+            .await?
+            .into_iter()
+            .map(|event| {
+                let added_at = first_seen.get(&event.id).copied().unwrap_or_else(|| {
+                    chrono::TimeZone::from_utc_datetime(
+                        &chrono::Utc,
+                        &event.time.and_time(Default::default()),
+                    )
+                });
+
+                feed::FeedEntry { event, added_at }
+            })
+            .collect::<Vec<_>>();
+
+        entries.sort_by(|a, b| {
+            b.added_at
+                .cmp(&a.added_at)
+                .then_with(|| b.event.sort_key().cmp(&a.event.sort_key()))
+        });
+        entries.truncate(feed::FEED_LENGTH);
+
+        Ok(entries)
+    }
+
+    /// A page of the events between `from` and `until`, continuing after
+    /// `cursor` if given. See [`pagination::paginate`].
+    pub async fn query_events(
+        &self,
+        from: chrono::NaiveDate,
+        until: chrono::NaiveDate,
+        order: SortOrder,
+        cursor: Option<&str>,
+        limit: Option<usize>,
+    ) -> eyre::Result<EventPage> {
+        check_range(from, until).map_err(|e| eyre::eyre!(e))?;
+        let events = self.get_events_between(from, until).await?;
+
+        pagination::paginate(events, order, cursor, limit)
+    }
+
     pub async fn get_event(&self, event_id: uuid::Uuid) -> eyre::Result<Option<Event>> {
-        let events = self.inner.events.read().await;
+        if let Some(event) = self.repository.get_event(event_id).await? {
+            return Ok(Some(event));
+        }
+
+        // Occurrences aren't stored, and their id can't be traced back to a
+        // date, so they are looked for around today.
+        let today = self.today();
+        let lookup = chrono::Duration::days(OCCURRENCE_LOOKUP_DAYS);
+        let recurring = self
+            .repository
+            .list_events()
+            .await?
+            .into_iter()
+            .filter(|e| e.recurrence.is_some())
+            .collect();
+
+        Ok(
+            recurrence::expand(recurring, today - lookup, today + lookup)
+                .into_iter()
+                .find(|e| e.id == event_id),
+        )
+    }
 
-        let event = events.iter().find(|e| e.id == event_id);
+    /// Finds an event by its slug. Occurrences of recurring events have the
+    /// slug of the event followed by their date.
+    pub async fn get_event_by_slug(&self, slug: &str) -> eyre::Result<Option<Event>> {
+        if let Some(event) = self.repository.get_event_by_slug(slug).await? {
+            return Ok(Some(event));
+        }
 
-        Ok(event.map(|e| e.clone()))
+        let Some((series_slug, date)) = slug
+            .len()
+            .checked_sub(10)
+            .and_then(|split| slug.get(..split)?.strip_suffix('-').zip(slug.get(split..)))
+        else {
+            return Ok(None);
+        };
+        let Ok(date) = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+            return Ok(None);
+        };
+
+        let series = self
+            .repository
+            .get_event_by_slug(series_slug)
+            .await?
+            .filter(|e| e.recurrence.is_some());
+
+        Ok(series.and_then(|series| recurrence::expand(vec![series], date, date).pop()))
     }
-}
 
-pub async fn fetch_events(event_path: PathBuf) -> eyre::Result<Vec<Event>> {
-    let mut dir = tokio::fs::read_dir(event_path).await?;
+    pub async fn get_attendees(&self, event_id: uuid::Uuid) -> eyre::Result<Attendees> {
+        Ok(Attendees {
+            rsvps: self.rsvps.list_rsvps(event_id).await?,
+        })
+    }
 
-    let mut events = vec![];
+    /// Records a guest's answer for the event, replacing their earlier answer.
+    pub async fn respond(
+        &self,
+        event_id: uuid::Uuid,
+        name: &str,
+        attendance: Attendance,
+        headcount: u32,
+        note: Option<String>,
+    ) -> eyre::Result<Rsvp> {
+        let (name, headcount, note) = rsvp::validate(name, headcount, note)?;
+
+        if self.get_event(event_id).await?.is_none() {
+            eyre::bail!("there is no such meal");
+        }
 
-    while let Ok(Some(entry)) = dir.next_entry().await {
-        let metadata = entry.metadata().await?;
-        if metadata.is_file() {
-            let file = tokio::fs::read(entry.path()).await?;
-            let content = std::str::from_utf8(&file)?;
-            if content.starts_with("---\n") {
-                let after_marker = &content[4..];
-                if let Some(marker_end) = after_marker.find("---\n") {
-                    let raw_front_matter = &content[4..marker_end + 4];
-                    let mut raw_event: RawEvent = serde_yaml::from_str(raw_front_matter)?;
-                    raw_event.content = content[marker_end + 4..].to_string();
+        self.rsvps
+            .put_rsvp(Rsvp {
+                id: uuid::Uuid::new_v4(),
+                event_id,
+                name,
+                attendance,
+                headcount,
+                note,
+                updated_at: chrono::Utc::now(),
+            })
+            .await
+    }
+
+    /// An event as it is written in the content repository, for editing.
+    /// Occurrences of recurring events edit the event they come from.
+    pub async fn get_editable_event(&self, slug: &str) -> eyre::Result<Option<EditableEvent>> {
+        let Some(event) = self.get_event_by_slug(slug).await? else {
+            return Ok(None);
+        };
+        let slug = match event.series_id {
+            Some(series_id) => match self.repository.get_event(series_id).await? {
+                Some(series) => series.slug,
+                None => return Ok(None),
+            },
+            None => event.slug,
+        };
+
+        let base_commit = self.repository.get_sync_status().await?.last_commit;
+        let Some(contents) = self.repository.read_event_file(&slug).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(EditableEvent {
+            draft: editor::read_draft(&contents)?,
+            slug,
+            base_commit,
+        }))
+    }
+
+    /// Writes the event to the content repository, as a new event when `slug`
+    /// is `None`. Returns the slug of the event.
+    pub async fn save_event(
+        &self,
+        author: &str,
+        slug: Option<&str>,
+        draft: EventDraft,
+        base_commit: Option<String>,
+    ) -> eyre::Result<String> {
+        check_base_commit(base_commit.as_deref())?;
+        let draft = editor::validate(draft)?;
+
+        let (slug, existing, message) = match slug {
+            Some(slug) => {
+                let existing = self
+                    .repository
+                    .read_event_file(slug)
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("there is no such meal"))?;
+
+                (
+                    slug.to_string(),
+                    Some(existing),
+                    format!("Update {}", draft.name),
+                )
+            }
+            None => {
+                let slug = self
+                    .unused_slug(editor::slug(draft.date, &draft.name))
+                    .await?;
 
-                    events.push(raw_event.into())
-                }
+                (slug, None, format!("Plan {} on {}", draft.name, draft.date))
             }
+        };
+
+        self.repository
+            .write_event_file(EventFileChange {
+                contents: Some(editor::render_file(existing.as_deref(), &draft)?),
+                slug: slug.clone(),
+                message,
+                author: author.to_string(),
+                base_commit,
+            })
+            .await?;
+
+        Ok(slug)
+    }
+
+    pub async fn delete_event(
+        &self,
+        author: &str,
+        slug: &str,
+        base_commit: Option<String>,
+    ) -> eyre::Result<()> {
+        check_base_commit(base_commit.as_deref())?;
+        let existing = self
+            .repository
+            .read_event_file(slug)
+            .await?
+            .ok_or_else(|| eyre::eyre!("there is no such meal"))?;
+        let message = match editor::read_draft(&existing) {
+            Ok(draft) => format!("Remove {} on {}", draft.name, draft.date),
+            Err(_) => format!("Remove {slug}"),
+        };
+
+        self.repository
+            .write_event_file(EventFileChange {
+                slug: slug.to_string(),
+                contents: None,
+                message,
+                author: author.to_string(),
+                base_commit,
+            })
+            .await
+    }
+
+    /// Events with the same name on the same day get a number after their
+    /// slug.
+    async fn unused_slug(&self, slug: String) -> eyre::Result<String> {
+        for n in 1.. {
+            let candidate = match n {
+                1 => slug.clone(),
+                n => format!("{slug}-{n}"),
+            };
+
+            if self.repository.read_event_file(&candidate).await?.is_none()
+                && self
+                    .repository
+                    .get_event_by_slug(&candidate)
+                    .await?
+                    .is_none()
+            {
+                return Ok(candidate);
+            }
+        }
+
+        unreachable!("there is always an unused slug")
+    }
+
+    /// How many people to cook for: the servings the meal is planned for, or
+    /// otherwise how many guests have said they are coming. `None` when
+    /// neither is known, and the recipe is used as written.
+    pub async fn planned_servings(&self, event: &Event) -> eyre::Result<Option<u32>> {
+        if let Some(servings) = event.servings.filter(|s| *s > 0) {
+            return Ok(Some(servings));
+        }
+
+        let headcount = self
+            .get_attendees(event.id)
+            .await?
+            .headcount(Attendance::Attending);
+
+        Ok((headcount > 0).then_some(headcount))
+    }
+
+    /// See [`EventStore::planned_servings`].
+    pub async fn get_planned_servings(&self, event_id: uuid::Uuid) -> eyre::Result<Option<u32>> {
+        match self.get_event(event_id).await? {
+            Some(event) => self.planned_servings(&event).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Everything needed for the meals planned between `from` and `until`,
+    /// with the items checked off on the list starting on `from`. Recipes are
+    /// scaled to the servings planned for each meal.
+    pub async fn get_shopping_list(
+        &self,
+        from: chrono::NaiveDate,
+        until: chrono::NaiveDate,
+    ) -> eyre::Result<ShoppingList> {
+        let mut recipes = Vec::new();
+        for event in self.get_events_between(from, until).await? {
+            let Some(recipe_id) = event.recipe_id else {
+                continue;
+            };
+            let Some(recipe) = self.repository.get_recipe(recipe_id).await? else {
+                continue;
+            };
+
+            recipes.push(match self.planned_servings(&event).await? {
+                Some(servings) => recipe.scaled_to(servings),
+                None => recipe,
+            });
+        }
+
+        let checked = self.shopping.list_checked(from).await?;
+        let mut items = shopping::build(&recipes);
+        for item in &mut items {
+            item.checked = checked.contains(&item.key);
         }
+
+        Ok(ShoppingList { from, until, items })
     }
 
-    Ok(events)
+    /// Marks an item on the list starting on `from` as bought, or not.
+    pub async fn check_shopping_item(
+        &self,
+        from: chrono::NaiveDate,
+        key: &str,
+        checked: bool,
+    ) -> eyre::Result<()> {
+        if key.is_empty() || key.len() > shopping::MAX_KEY_LENGTH {
+            eyre::bail!("there is no such item");
+        }
+
+        self.shopping.set_checked(from, key, checked).await
+    }
+
+    pub async fn get_recipe(&self, recipe_id: uuid::Uuid) -> eyre::Result<Option<Recipe>> {
+        self.repository.get_recipe(recipe_id).await
+    }
+
+    pub async fn get_recipe_by_slug(&self, slug: &str) -> eyre::Result<Option<Recipe>> {
+        self.repository.get_recipe_by_slug(slug).await
+    }
+
+    pub async fn get_sync_status(&self) -> eyre::Result<SyncStatus> {
+        self.repository.get_sync_status().await
+    }
+
+    /// Problems found in the content repository during the last sync.
+    pub async fn get_diagnostics(&self) -> eyre::Result<Vec<ParseDiagnostic>> {
+        self.repository.get_diagnostics().await
+    }
+}
+
+/// Content is synced from git when a repository url is configured, and read
+/// from the local filesystem otherwise.
+fn content_repository(config: ContentConfig) -> Arc<dyn EventRepository> {
+    match config.repo_url.clone() {
+        Some(repo_url) => Arc::new(GitEventRepository::new(config, repo_url)),
+        None => Arc::new(FileSystemEventRepository::new(config)),
+    }
+}
+
+pub async fn fetch_events(event_path: PathBuf) -> eyre::Result<(Vec<Event>, Vec<ParseDiagnostic>)> {
+    let mut diagnostics = vec![];
+
+    let events = read_documents::<RawEvent>(&event_path, &mut diagnostics)
+        .await?
+        .into_iter()
+        .map(event_from_document)
+        .collect();
+
+    Ok((events, diagnostics))
+}
+
+pub(crate) fn event_from_document(document: Document<RawEvent>) -> Event {
+    let mut raw_event = document.front_matter;
+    raw_event.slug = document.slug;
+    raw_event.content = document.body;
+
+    raw_event.into()
 }
 
 impl Default for EventStore {
     fn default() -> Self {
-        Self {
-            inner: Arc::new(InnerEventStore {
-                url: Default::default(),
-                path: PathBuf::from("articles"),
-                events: Default::default(),
-                url_path: Some("articles/events".into()),
-            }),
-        }
+        Self::with_repository(
+            Arc::new(InMemoryEventRepository::default()),
+            chrono_tz::Tz::UTC,
+        )
     }
 }
 
 #[cfg(test)]
 mod test {
-    use domain::Event;
+    use chrono::{NaiveDate, NaiveTime};
+    use domain::{Attendance, Event, MealSlot, Recipe, SortOrder};
+
+    use std::sync::Arc;
 
-    use crate::RawEvent;
+    use crate::recipes::RawRecipe;
+    use crate::{
+        check_base_commit, event_id_from_slug, EventStore, InMemoryEventRepository, RawEvent,
+        MAX_RANGE_DAYS,
+    };
 
     #[test]
     fn can_parse_event() {
@@ -231,4 +746,207 @@ time: 2023-03-06"#;
         let raw_event: RawEvent = serde_yaml::from_str(raw).unwrap();
         let _: Event = raw_event.into();
     }
+
+    #[test]
+    fn event_id_is_stable_across_parses() {
+        let raw = r#"name: Gammeldags oksesteg
+coverImage:
+  url: https://example.com/oksesteg.jpg
+  alt: billede af oksesteg
+time: 2023-03-06"#;
+
+        let parse = || {
+            let mut raw_event: RawEvent = serde_yaml::from_str(raw).unwrap();
+            raw_event.slug = "2023-03-06-gammeldags-oksesteg".into();
+            let event: Event = raw_event.into();
+            event
+        };
+
+        let first = parse();
+        let second = parse();
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(
+            first.id,
+            event_id_from_slug("2023-03-06-gammeldags-oksesteg")
+        );
+        assert_eq!(
+            first.cover_image.map(|i| i.id),
+            second.cover_image.map(|i| i.id)
+        );
+    }
+
+    #[test]
+    fn can_parse_time_of_day_and_meal() {
+        let raw = r#"name: Gammeldags oksesteg
+time: 2023-03-06 18:30
+duration: 90
+meal: dinner"#;
+
+        let raw_event: RawEvent = serde_yaml::from_str(raw).unwrap();
+        let event: Event = raw_event.into();
+
+        assert_eq!(event.time, NaiveDate::from_ymd_opt(2023, 3, 6).unwrap());
+        assert_eq!(event.start_time, NaiveTime::from_hms_opt(18, 30, 0));
+        assert_eq!(event.duration_minutes, Some(90));
+        assert_eq!(event.meal, Some(MealSlot::Dinner));
+
+        let raw_event: RawEvent =
+            serde_yaml::from_str("name: Boller\ntime: 2023-03-06T08:00").unwrap();
+        assert_eq!(raw_event.time.time_of_day, NaiveTime::from_hms_opt(8, 0, 0));
+
+        let raw_event: RawEvent = serde_yaml::from_str("name: Boller\ntime: 2023-03-06").unwrap();
+        assert_eq!(raw_event.time.time_of_day, None);
+
+        assert!(serde_yaml::from_str::<RawEvent>("name: Boller\ntime: tomorrow").is_err());
+    }
+
+    #[tokio::test]
+    async fn occurrences_can_be_found_by_id_and_slug() {
+        let store = EventStore::default();
+        let start = store.today() - chrono::Duration::days(14);
+        let raw = format!(
+            "name: Pizza\ntime: {}\nrecurrence: FREQ=WEEKLY",
+            start.format("%Y-%m-%d")
+        );
+        let mut raw_event: RawEvent = serde_yaml::from_str(&raw).unwrap();
+        raw_event.slug = "pizza".into();
+        let event: Event = raw_event.into();
+
+        let store = EventStore::with_repository(
+            Arc::new(InMemoryEventRepository::new(vec![event.clone()], vec![])),
+            chrono_tz::Tz::UTC,
+        );
+
+        let upcoming = store.get_upcoming_events().await.unwrap();
+        assert_eq!(upcoming.len(), 13);
+        assert_eq!(upcoming[0].time, start + chrono::Duration::days(14));
+
+        let by_id = store.get_event(upcoming[0].id).await.unwrap().unwrap();
+        assert_eq!(by_id, upcoming[0]);
+        let by_slug = store
+            .get_event_by_slug(&upcoming[0].slug)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_slug, upcoming[0]);
+
+        let not_an_occurrence = format!(
+            "pizza-{}",
+            (start + chrono::Duration::days(1)).format("%Y-%m-%d")
+        );
+        assert!(store
+            .get_event_by_slug(&not_an_occurrence)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn queries_are_limited_to_a_range_of_dates() {
+        let store = EventStore::default();
+        let from = chrono::NaiveDate::from_ymd_opt(2023, 3, 6).unwrap();
+        let query = |until| store.query_events(from, until, SortOrder::Ascending, None, None);
+
+        assert!(query(from).await.is_ok());
+        assert!(query(from + chrono::Duration::days(MAX_RANGE_DAYS))
+            .await
+            .is_ok());
+        assert!(query(from - chrono::Duration::days(1)).await.is_err());
+        assert!(query(chrono::NaiveDate::MAX).await.is_err());
+    }
+
+    #[test]
+    fn base_commits_must_be_full_commit_ids() {
+        assert!(check_base_commit(None).is_ok());
+        assert!(check_base_commit(Some("0123456789abcdef0123456789abcdef01234567")).is_ok());
+
+        for commit in [
+            "",
+            "HEAD",
+            "0123456",
+            "--output=/tmp/x",
+            "0123456789ABCDEF0123456789ABCDEF01234567",
+            "0123456789abcdef0123456789abcdef01234567^",
+        ] {
+            assert!(check_base_commit(Some(commit)).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn shopping_lists_follow_the_planned_servings_or_the_headcount() {
+        let mut raw_recipe: RawRecipe = serde_yaml::from_str(
+            "name: Oksesteg\nservings: 4\ningredients:\n  - 1,5 kg oksesteg\n  - salt",
+        )
+        .unwrap();
+        raw_recipe.slug = "oksesteg".into();
+        let recipe: Recipe = raw_recipe.into();
+
+        let event = |slug: &str, front_matter: &str| {
+            let mut raw_event: RawEvent = serde_yaml::from_str(front_matter).unwrap();
+            raw_event.slug = slug.into();
+            Event::from(raw_event)
+        };
+        let planned = event(
+            "2023-03-06-oksesteg",
+            "name: Oksesteg\ntime: 2023-03-06\nrecipe: oksesteg\nservings: 6",
+        );
+        let with_guests = event(
+            "2023-03-08-oksesteg",
+            "name: Oksesteg\ntime: 2023-03-08\nrecipe: oksesteg",
+        );
+        let no_servings = event(
+            "2023-03-08-oksesteg",
+            "name: Oksesteg\ntime: 2023-03-08\nrecipe: oksesteg\nservings: 0",
+        );
+
+        let store = EventStore::with_repository(
+            Arc::new(InMemoryEventRepository::new(
+                vec![planned.clone(), with_guests.clone()],
+                vec![recipe],
+            )),
+            chrono_tz::Tz::UTC,
+        );
+        store
+            .respond(with_guests.id, "Mormor", Attendance::Attending, 2, None)
+            .await
+            .unwrap();
+        store
+            .respond(with_guests.id, "Morfar", Attendance::Maybe, 1, None)
+            .await
+            .unwrap();
+
+        assert_eq!(store.planned_servings(&planned).await.unwrap(), Some(6));
+        assert_eq!(store.planned_servings(&with_guests).await.unwrap(), Some(2));
+        assert_eq!(store.planned_servings(&no_servings).await.unwrap(), Some(2));
+
+        let from = NaiveDate::from_ymd_opt(2023, 3, 6).unwrap();
+        let list = store
+            .get_shopping_list(from, from + chrono::Duration::days(6))
+            .await
+            .unwrap();
+
+        // 2.25 kg for six people and 0.75 kg for two.
+        assert_eq!(list.items.len(), 2);
+        assert_eq!(list.items[0].name, "oksesteg");
+        assert_eq!(list.items[0].quantity, Some(3.0));
+        assert_eq!(list.items[0].unit.as_deref(), Some("kg"));
+        assert_eq!(list.items[1].quantity, None);
+    }
+
+    #[test]
+    fn event_id_from_front_matter_takes_precedence() {
+        let raw = r#"id: 4f7c7f0e-8f3a-4a39-9a3e-2a7a3f8d6c11
+name: Gammeldags oksesteg
+time: 2023-03-06"#;
+
+        let mut raw_event: RawEvent = serde_yaml::from_str(raw).unwrap();
+        raw_event.slug = "2023-03-06-gammeldags-oksesteg".into();
+        let event: Event = raw_event.into();
+
+        assert_eq!(
+            event.id,
+            uuid::Uuid::parse_str("4f7c7f0e-8f3a-4a39-9a3e-2a7a3f8d6c11").unwrap()
+        );
+    }
 }
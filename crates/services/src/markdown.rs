@@ -0,0 +1,64 @@
+use pulldown_cmark::{html, Options, Parser};
+
+/// Renders the markdown body of an article to html. The content repository is
+/// not trusted to be well-behaved, so the output is sanitized before it is
+/// handed to the browser.
+pub fn render_markdown(markdown: &str) -> Option<String> {
+    if markdown.trim().is_empty() {
+        return None;
+    }
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let parser = Parser::new_ext(markdown, options);
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+
+    Some(ammonia::clean(&unsafe_html))
+}
+
+#[cfg(test)]
+mod test {
+    use super::render_markdown;
+
+    #[test]
+    fn renders_common_markdown() {
+        let html = render_markdown(
+            r#"# Oksesteg
+
+Serveres med:
+
+- kartofler
+- syltede agurker
+
+Se [opskriften](https://example.com/oksesteg).
+
+![stegen](https://example.com/oksesteg.jpg)"#,
+        )
+        .unwrap();
+
+        assert!(html.contains("<h1>Oksesteg</h1>"));
+        assert!(html.contains("<li>kartofler</li>"));
+        assert!(html.contains(r#"href="https://example.com/oksesteg""#));
+        assert!(html.contains(r#"src="https://example.com/oksesteg.jpg""#));
+    }
+
+    #[test]
+    fn strips_unsafe_html() {
+        let html = render_markdown(
+            "Hej <script>alert('hej')</script><a href=\"javascript:alert(1)\">x</a>",
+        )
+        .unwrap();
+
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn empty_body_renders_nothing() {
+        assert_eq!(render_markdown("\n  \n"), None);
+    }
+}
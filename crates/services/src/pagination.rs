@@ -0,0 +1,149 @@
+use chrono::{NaiveDate, NaiveTime};
+use domain::{Event, EventPage, SortOrder};
+
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// Position of the last event on a page. The id breaks ties between events at
+/// the same time, so every event has a distinct position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Cursor {
+    date: NaiveDate,
+    time: Option<NaiveTime>,
+    id: uuid::Uuid,
+}
+
+impl Cursor {
+    fn of(event: &Event) -> Self {
+        let (date, time) = event.sort_key();
+
+        Self {
+            date,
+            time,
+            id: event.id,
+        }
+    }
+
+    /// Encoded as `date|time|id`, where the time is left empty when there is
+    /// none.
+    fn encode(&self) -> String {
+        format!(
+            "{}|{}|{}",
+            self.date.format("%Y-%m-%d"),
+            self.time
+                .map(|t| t.format("%H:%M:%S").to_string())
+                .unwrap_or_default(),
+            self.id
+        )
+    }
+
+    fn decode(cursor: &str) -> eyre::Result<Self> {
+        let invalid = || eyre::eyre!("invalid cursor {cursor}");
+
+        let mut parts = cursor.split('|');
+        let (Some(date), Some(time), Some(id), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        Ok(Self {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| invalid())?,
+            time: match time {
+                "" => None,
+                time => Some(NaiveTime::parse_from_str(time, "%H:%M:%S").map_err(|_| invalid())?),
+            },
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Orders the events and returns the page following `cursor`, or the first
+/// page without one.
+pub fn paginate(
+    mut events: Vec<Event>,
+    order: SortOrder,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> eyre::Result<EventPage> {
+    let cursor = cursor.map(Cursor::decode).transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    events.sort_by_key(Cursor::of);
+    if order == SortOrder::Descending {
+        events.reverse();
+    }
+
+    let mut page: Vec<Event> = events
+        .into_iter()
+        .filter(|event| match (cursor, order) {
+            (None, _) => true,
+            (Some(cursor), SortOrder::Ascending) => Cursor::of(event) > cursor,
+            (Some(cursor), SortOrder::Descending) => Cursor::of(event) < cursor,
+        })
+        .take(limit + 1)
+        .collect();
+
+    let next_cursor = if page.len() > limit {
+        page.truncate(limit);
+        page.last().map(|event| Cursor::of(event).encode())
+    } else {
+        None
+    };
+
+    Ok(EventPage {
+        events: page.into_iter().map(|event| event.into()).collect(),
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use domain::{Event, SortOrder};
+
+    use crate::RawEvent;
+
+    use super::paginate;
+
+    fn event(slug: &str, time: &str) -> Event {
+        let mut raw_event: RawEvent =
+            serde_yaml::from_str(&format!("name: {slug}\ntime: {time}")).unwrap();
+        raw_event.slug = slug.into();
+        raw_event.into()
+    }
+
+    #[test]
+    fn pages_follow_each_other_without_gaps() {
+        let events = vec![
+            event("frikadeller", "2023-03-07"),
+            event("boller", "2023-03-06 08:00"),
+            event("oksesteg", "2023-03-06 18:30"),
+            event("pizza", "2023-03-10"),
+            event("lasagne", "2023-03-08"),
+        ];
+
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            let mut slugs = Vec::new();
+            let mut cursor = None;
+            loop {
+                let page = paginate(events.clone(), order, cursor.as_deref(), Some(2)).unwrap();
+                slugs.extend(page.events.into_iter().map(|e| e.slug));
+                cursor = page.next_cursor;
+                if cursor.is_none() {
+                    break;
+                }
+            }
+
+            let mut expected = vec!["boller", "oksesteg", "frikadeller", "lasagne", "pizza"];
+            if order == SortOrder::Descending {
+                expected.reverse();
+            }
+            assert_eq!(slugs, expected);
+        }
+    }
+
+    #[test]
+    fn invalid_cursors_are_rejected() {
+        assert!(paginate(vec![], SortOrder::Ascending, Some("yesterday"), None).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use domain::{Event, Ingredient, Metadata, ParseDiagnostic, Recipe, Reference, Step};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::{cooklang, front_matter::read_documents, markdown::render_markdown, RawImage};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RawRecipe {
+    pub id: Option<uuid::Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    pub servings: Option<u32>,
+    #[serde(alias = "prepTime")]
+    pub prep_time: Option<u32>,
+    #[serde(alias = "cookTime")]
+    pub cook_time: Option<u32>,
+    #[serde(default)]
+    pub ingredients: Vec<RawIngredient>,
+    #[serde(default)]
+    pub steps: Vec<String>,
+    #[serde(default)]
+    pub references: Vec<RawReference>,
+    #[serde(default)]
+    pub images: Vec<RawImage>,
+    pub metadata: Option<Metadata>,
+    #[serde(skip)]
+    pub slug: String,
+    #[serde(skip)]
+    pub content: String,
+}
+
+/// An ingredient is either written out in full, or on a single line such as
+/// `2 spsk smør` or `salt`, see [`Ingredient::parse`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RawIngredient {
+    Name(String),
+    Detailed {
+        name: String,
+        quantity: Option<f64>,
+        unit: Option<String>,
+        note: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RawReference {
+    Url(String),
+    Detailed { title: Option<String>, url: String },
+}
+
+/// Namespace used to derive recipe ids from their slug, see
+/// [`crate::event_id_from_slug`].
+const RECIPE_NAMESPACE: uuid::Uuid =
+    uuid::Uuid::from_u128(0x2d4f_8a61_b0c7_4e93_8f1a_5c6e_d27b_904f);
+
+pub fn recipe_id_from_slug(slug: &str) -> uuid::Uuid {
+    uuid::Uuid::new_v5(&RECIPE_NAMESPACE, slug.as_bytes())
+}
+
+impl From<RawIngredient> for Ingredient {
+    fn from(value: RawIngredient) -> Self {
+        match value {
+            RawIngredient::Name(line) => Ingredient::parse(&line),
+            RawIngredient::Detailed {
+                name,
+                quantity,
+                unit,
+                note,
+            } => Self {
+                name,
+                quantity,
+                unit,
+                note,
+            },
+        }
+    }
+}
+
+impl From<RawReference> for Reference {
+    fn from(value: RawReference) -> Self {
+        match value {
+            RawReference::Url(url) => Self { title: None, url },
+            RawReference::Detailed { title, url } => Self { title, url },
+        }
+    }
+}
+
+impl From<RawRecipe> for Recipe {
+    fn from(value: RawRecipe) -> Self {
+        let id = value.id.unwrap_or_else(|| recipe_id_from_slug(&value.slug));
+
+        Self {
+            id,
+            slug: value.slug,
+            name: value.name,
+            description: value.description,
+            servings: value.servings,
+            prep_time_minutes: value.prep_time,
+            cook_time_minutes: value.cook_time,
+            ingredients: value.ingredients.into_iter().map(|i| i.into()).collect(),
+            steps: value.steps.into_iter().map(|text| Step { text }).collect(),
+            references: value.references.into_iter().map(|r| r.into()).collect(),
+            images: value.images.into_iter().map(|i| i.into_image(id)).collect(),
+            metadata: value.metadata,
+            body_html: render_markdown(&value.content),
+        }
+    }
+}
+
+/// Events referring to a recipe by slug get the id derived from that slug. If
+/// the recipe pins its own id in the front matter, point the events at it.
+pub fn link_recipes(events: &mut [Event], recipes: &[Recipe]) {
+    for recipe in recipes {
+        let derived_id = recipe_id_from_slug(&recipe.slug);
+        if derived_id == recipe.id {
+            continue;
+        }
+
+        for event in events.iter_mut() {
+            if event.recipe_id == Some(derived_id) {
+                event.recipe_id = Some(recipe.id);
+            }
+        }
+    }
+}
+
+pub async fn fetch_recipes(
+    recipe_path: PathBuf,
+) -> eyre::Result<(Vec<Recipe>, Vec<ParseDiagnostic>)> {
+    if !tokio::fs::try_exists(&recipe_path).await? {
+        tracing::debug!(
+            path = recipe_path.display().to_string(),
+            "no recipe directory, skipping"
+        );
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let mut diagnostics = vec![];
+
+    let mut recipes: Vec<Recipe> = read_documents::<RawRecipe>(&recipe_path, &mut diagnostics)
+        .await?
+        .into_iter()
+        .map(|document| {
+            let mut raw_recipe = document.front_matter;
+            raw_recipe.slug = document.slug;
+            raw_recipe.content = document.body;
+
+            raw_recipe.into()
+        })
+        .collect();
+
+    let cook_recipes = cooklang::read_recipes(&recipe_path, &mut diagnostics).await?;
+    recipes.extend(cook_recipes.into_iter().map(Recipe::from));
+
+    Ok((recipes, diagnostics))
+}
+
+#[cfg(test)]
+mod test {
+    use domain::{Event, Recipe};
+
+    use crate::{
+        recipes::{link_recipes, recipe_id_from_slug, RawRecipe},
+        RawEvent,
+    };
+
+    #[test]
+    fn can_parse_recipe() {
+        let raw = r#"name: Gammeldags oksesteg
+servings: 4
+prepTime: 20
+cookTime: 120
+ingredients:
+  - name: oksesteg
+    quantity: 1.5
+    unit: kg
+  - salt
+  - 1½ dl fløde, pisket
+steps:
+  - Brun stegen i en gryde.
+  - Lad den simre i 2 timer.
+references:
+  - https://www.arla.dk/opskrifter/gammeldags-oksesteg/
+  - title: Brun sauce
+    url: https://example.com/brun-sauce"#;
+
+        let mut raw_recipe: RawRecipe = serde_yaml::from_str(raw).unwrap();
+        raw_recipe.slug = "gammeldags-oksesteg".into();
+        let recipe: Recipe = raw_recipe.into();
+
+        assert_eq!(recipe.id, recipe_id_from_slug("gammeldags-oksesteg"));
+        assert_eq!(recipe.servings, Some(4));
+        assert_eq!(recipe.total_time_minutes(), Some(140));
+        assert_eq!(recipe.ingredients.len(), 3);
+        assert_eq!(recipe.ingredients[0].quantity, Some(1.5));
+        assert_eq!(recipe.ingredients[1].name, "salt");
+        assert_eq!(recipe.ingredients[1].quantity, None);
+        assert_eq!(recipe.ingredients[2].name, "fløde");
+        assert_eq!(recipe.ingredients[2].quantity, Some(1.5));
+        assert_eq!(recipe.ingredients[2].unit.as_deref(), Some("dl"));
+        assert_eq!(recipe.ingredients[2].note.as_deref(), Some("pisket"));
+        assert_eq!(recipe.steps[1].text, "Lad den simre i 2 timer.");
+        assert_eq!(recipe.references[1].title.as_deref(), Some("Brun sauce"));
+        assert_eq!(recipe.body_html, None);
+    }
+
+    #[test]
+    fn recipe_body_is_rendered() {
+        let mut raw_recipe: RawRecipe = serde_yaml::from_str("name: Brun sauce").unwrap();
+        raw_recipe.slug = "brun-sauce".into();
+        raw_recipe.content = "Brug *fonden* fra stegen.\n\n<script>alert(1)</script>".into();
+        let recipe: Recipe = raw_recipe.into();
+
+        assert_eq!(
+            recipe.body_html.as_deref(),
+            Some("<p>Brug <em>fonden</em> fra stegen.</p>\n")
+        );
+    }
+
+    #[test]
+    fn events_link_to_recipes_by_slug() {
+        let mut raw_recipe: RawRecipe = serde_yaml::from_str(
+            r#"id: 0b0e5a4e-3a7d-4d38-9a43-4c7f4a1d2b6e
+name: Gammeldags oksesteg"#,
+        )
+        .unwrap();
+        raw_recipe.slug = "gammeldags-oksesteg".into();
+        let recipe: Recipe = raw_recipe.into();
+
+        let mut raw_event: RawEvent = serde_yaml::from_str(
+            r#"name: Gammeldags oksesteg
+time: 2023-03-06
+recipe: gammeldags-oksesteg"#,
+        )
+        .unwrap();
+        raw_event.slug = "2023-03-06-gammeldags-oksesteg".into();
+        let event: Event = raw_event.into();
+
+        let mut events = vec![event];
+        link_recipes(&mut events, std::slice::from_ref(&recipe));
+
+        assert_eq!(events[0].recipe_id, Some(recipe.id));
+    }
+}
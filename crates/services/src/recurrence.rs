@@ -0,0 +1,606 @@
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use domain::{Event, Recurrence};
+use serde::{Deserialize, Serialize};
+
+/// Rules producing nothing, such as the 30th of February, would otherwise be
+/// searched forever.
+const MAX_PERIODS: u32 = 10_000;
+
+/// Intervals beyond this are typos rather than plans.
+const MAX_INTERVAL: u32 = 1_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The subset of an RFC 5545 RRULE supported in front matter, e.g.
+/// `FREQ=MONTHLY;BYDAY=1SU` for the first Sunday of every month.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<NaiveDate>,
+    /// Weekdays, optionally with the position within the month or year, such
+    /// as `-1FR` for the last Friday.
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("RRULE:").unwrap_or(s);
+
+        let mut frequency = None;
+        let mut rule = RecurrenceRule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+
+        for part in s.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected KEY=VALUE, found {part}"))?;
+
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => return Err(format!("unsupported frequency {other}")),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|i| (1..=MAX_INTERVAL).contains(i))
+                        .ok_or_else(|| format!("invalid interval {value}"))?
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid count {value}"))?,
+                    )
+                }
+                "UNTIL" => rule.until = Some(parse_until(value)?),
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = value
+                        .split(',')
+                        .map(|d| {
+                            d.parse()
+                                .ok()
+                                .filter(|d: &i32| (1..=31).contains(&d.abs()))
+                                .ok_or_else(|| format!("invalid day of month {d}"))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTH" => {
+                    rule.by_month = value
+                        .split(',')
+                        .map(|m| {
+                            m.parse()
+                                .ok()
+                                .filter(|m| (1..=12).contains(m))
+                                .ok_or_else(|| format!("invalid month {m}"))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                "WKST" if value.eq_ignore_ascii_case("MO") => {}
+                other => return Err(format!("unsupported rule part {other}")),
+            }
+        }
+
+        rule.frequency = frequency.ok_or_else(|| "missing FREQ".to_string())?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("COUNT and UNTIL can't be used together".into());
+        }
+        if rule.frequency != Frequency::Monthly
+            && rule.frequency != Frequency::Yearly
+            && rule.by_day.iter().any(|(n, _)| n.is_some())
+        {
+            return Err("numbered BYDAY is only allowed for monthly and yearly rules".into());
+        }
+
+        Ok(rule)
+    }
+}
+
+fn parse_until(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.get(..8).unwrap_or(value), "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+        .map_err(|_| format!("invalid until {value}"))
+}
+
+fn parse_by_day(value: &str) -> Result<(Option<i32>, Weekday), String> {
+    let invalid = || format!("invalid day {value}");
+    let split = value.len().checked_sub(2).ok_or_else(invalid)?;
+    let (ordinal, day) = value.split_at(split);
+
+    let weekday = match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(invalid()),
+    };
+    let ordinal = match ordinal {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .parse::<i32>()
+                .ok()
+                .filter(|n| *n != 0 && n.abs() <= 53)
+                .ok_or_else(invalid)?,
+        ),
+    };
+
+    Ok((ordinal, weekday))
+}
+
+impl RecurrenceRule {
+    /// Dates the rule produces for a series starting at `start`, limited to
+    /// `from..=until` and leaving out the exceptions. The start date is always
+    /// the first occurrence.
+    pub fn occurrences(
+        &self,
+        start: NaiveDate,
+        except: &[NaiveDate],
+        from: NaiveDate,
+        until: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        let until = match self.until {
+            Some(rule_until) => rule_until.min(until),
+            None => until,
+        };
+
+        let mut dates = Vec::new();
+        let mut produced = 0;
+
+        for period in 0..MAX_PERIODS {
+            // The series ends with the last date that can be represented.
+            let Some(mut candidates) = period
+                .checked_mul(self.interval)
+                .and_then(|offset| self.candidates(start, offset))
+            else {
+                break;
+            };
+            if period == 0 {
+                candidates.push(start);
+            }
+            candidates.sort();
+            candidates.dedup();
+
+            for date in candidates.into_iter().filter(|d| *d >= start) {
+                if date > until || self.count.map(|c| produced >= c).unwrap_or(false) {
+                    return dates;
+                }
+                produced += 1;
+
+                if date >= from && !except.contains(&date) {
+                    dates.push(date);
+                }
+            }
+        }
+
+        dates
+    }
+
+    /// Dates matching the rule within the period `offset` periods after the
+    /// one containing `start`, or `None` if that period is out of range.
+    fn candidates(&self, start: NaiveDate, offset: u32) -> Option<Vec<NaiveDate>> {
+        let candidates = match self.frequency {
+            Frequency::Daily => {
+                let date = start.checked_add_signed(Duration::days(offset as i64))?;
+                vec![date]
+                    .into_iter()
+                    .filter(|d| self.by_day.is_empty() || self.matches_weekday(*d))
+                    .filter(|d| self.by_month_day.is_empty() || self.matches_month_day(*d))
+                    .collect()
+            }
+            Frequency::Weekly => {
+                let weekday = start.weekday().num_days_from_monday() as i64;
+                let monday = start
+                    .checked_sub_signed(Duration::days(weekday))?
+                    .checked_add_signed(Duration::weeks(offset as i64))?;
+                if self.by_day.is_empty() {
+                    vec![monday.checked_add_signed(Duration::days(weekday))?]
+                } else {
+                    self.by_day
+                        .iter()
+                        .filter_map(|(_, day)| {
+                            monday.checked_add_signed(Duration::days(
+                                day.num_days_from_monday() as i64
+                            ))
+                        })
+                        .collect()
+                }
+            }
+            Frequency::Monthly => {
+                let (year, month) = add_months(start.year(), start.month(), offset)?;
+                self.days_in_month(start, year, month)
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(offset).ok()?)?;
+                if !self.by_month.is_empty() {
+                    self.by_month
+                        .iter()
+                        .flat_map(|month| self.days_in_month(start, year, *month))
+                        .collect()
+                } else if !self.by_month_day.is_empty() {
+                    (1..=12)
+                        .flat_map(|month| self.days_in_month(start, year, month))
+                        .collect()
+                } else if !self.by_day.is_empty() {
+                    let days = days_between(
+                        NaiveDate::from_ymd_opt(year, 1, 1),
+                        NaiveDate::from_ymd_opt(year, 12, 31),
+                    );
+                    self.select_weekdays(&days)
+                } else {
+                    NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                        .into_iter()
+                        .collect()
+                }
+            }
+        };
+
+        Some(
+            candidates
+                .into_iter()
+                .filter(|d| self.by_month.is_empty() || self.by_month.contains(&d.month()))
+                .collect(),
+        )
+    }
+
+    fn days_in_month(&self, start: NaiveDate, year: i32, month: u32) -> Vec<NaiveDate> {
+        let days = days_between(
+            NaiveDate::from_ymd_opt(year, month, 1),
+            last_day_of_month(year, month),
+        );
+
+        match (self.by_month_day.is_empty(), self.by_day.is_empty()) {
+            (true, true) => days
+                .into_iter()
+                .filter(|d| d.day() == start.day())
+                .collect(),
+            (false, true) => days
+                .into_iter()
+                .filter(|d| self.matches_month_day(*d))
+                .collect(),
+            (true, false) => self.select_weekdays(&days),
+            (false, false) => self
+                .select_weekdays(&days)
+                .into_iter()
+                .filter(|d| self.matches_month_day(*d))
+                .collect(),
+        }
+    }
+
+    /// Picks the days matching BYDAY out of a month or year, where `2MO` is
+    /// the second Monday and `-1MO` the last.
+    fn select_weekdays(&self, days: &[NaiveDate]) -> Vec<NaiveDate> {
+        self.by_day
+            .iter()
+            .flat_map(|(ordinal, weekday)| {
+                let matching: Vec<NaiveDate> = days
+                    .iter()
+                    .copied()
+                    .filter(|d| d.weekday() == *weekday)
+                    .collect();
+
+                match ordinal {
+                    None => matching,
+                    Some(n) if *n > 0 => {
+                        matching.get(*n as usize - 1).copied().into_iter().collect()
+                    }
+                    Some(n) => matching
+                        .len()
+                        .checked_sub(n.unsigned_abs() as usize)
+                        .and_then(|i| matching.get(i).copied())
+                        .into_iter()
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    fn matches_weekday(&self, date: NaiveDate) -> bool {
+        self.by_day.iter().any(|(_, day)| *day == date.weekday())
+    }
+
+    fn matches_month_day(&self, date: NaiveDate) -> bool {
+        let days_in_month = last_day_of_month(date.year(), date.month())
+            .map(|d| d.day() as i32)
+            .unwrap_or(31);
+
+        self.by_month_day.iter().any(|day| {
+            let day = if *day < 0 {
+                days_in_month + day + 1
+            } else {
+                *day
+            };
+            day == date.day() as i32
+        })
+    }
+}
+
+fn add_months(year: i32, month: u32, offset: u32) -> Option<(i32, u32)> {
+    let months = year
+        .checked_mul(12)?
+        .checked_add(month as i32 - 1)?
+        .checked_add(i32::try_from(offset).ok()?)?;
+    Some((months.div_euclid(12), months.rem_euclid(12) as u32 + 1))
+}
+
+fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let (next_year, next_month) = add_months(year, month, 1)?;
+    NaiveDate::from_ymd_opt(next_year, next_month, 1).and_then(|d| d.pred_opt())
+}
+
+fn days_between(first: Option<NaiveDate>, last: Option<NaiveDate>) -> Vec<NaiveDate> {
+    match (first, last) {
+        (Some(first), Some(last)) => first.iter_days().take_while(|d| *d <= last).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `recurrence` in front matter, either just the rule or the rule together
+/// with dates to skip.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "RawRecurrenceDef")]
+pub struct RawRecurrence {
+    pub rule: String,
+    pub except: Vec<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawRecurrenceDef {
+    Rule(String),
+    Detailed {
+        rule: String,
+        #[serde(default)]
+        except: Vec<NaiveDate>,
+    },
+}
+
+impl TryFrom<RawRecurrenceDef> for RawRecurrence {
+    type Error = String;
+
+    fn try_from(value: RawRecurrenceDef) -> Result<Self, Self::Error> {
+        let (rule, except) = match value {
+            RawRecurrenceDef::Rule(rule) => (rule, Vec::new()),
+            RawRecurrenceDef::Detailed { rule, except } => (rule, except),
+        };
+
+        rule.parse::<RecurrenceRule>()
+            .map_err(|e| format!("invalid recurrence {rule}: {e}"))?;
+
+        Ok(Self { rule, except })
+    }
+}
+
+impl From<RawRecurrence> for Recurrence {
+    fn from(value: RawRecurrence) -> Self {
+        Self {
+            rule: value.rule,
+            except: value.except,
+        }
+    }
+}
+
+/// Occurrences of a recurring event get an id derived from the event and the
+/// date, so they keep it between expansions.
+pub fn occurrence_id(series_id: uuid::Uuid, date: NaiveDate) -> uuid::Uuid {
+    uuid::Uuid::new_v5(&series_id, date.format("%Y-%m-%d").to_string().as_bytes())
+}
+
+/// Replaces recurring events with their occurrences within `from..=until`.
+/// Events which don't recur are kept as they are.
+pub fn expand(events: Vec<Event>, from: NaiveDate, until: NaiveDate) -> Vec<Event> {
+    events
+        .into_iter()
+        .flat_map(|event| match &event.recurrence {
+            Some(recurrence) => occurrences(&event, recurrence, from, until),
+            None => vec![event],
+        })
+        .collect()
+}
+
+fn occurrences(
+    event: &Event,
+    recurrence: &Recurrence,
+    from: NaiveDate,
+    until: NaiveDate,
+) -> Vec<Event> {
+    let rule = match recurrence.rule.parse::<RecurrenceRule>() {
+        Ok(rule) => rule,
+        Err(e) => {
+            tracing::warn!(slug = event.slug, error = e, "invalid recurrence");
+            return Vec::new();
+        }
+    };
+
+    rule.occurrences(event.time, &recurrence.except, from, until)
+        .into_iter()
+        .map(|date| Event {
+            id: occurrence_id(event.id, date),
+            slug: format!("{}-{}", event.slug, date.format("%Y-%m-%d")),
+            time: date,
+            recurrence: None,
+            series_id: Some(event.id),
+            ..event.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use domain::Event;
+
+    use crate::RawEvent;
+
+    use super::{expand, occurrence_id, RecurrenceRule};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn dates(rule: &str, start: &str, except: &[&str], until: &str) -> Vec<String> {
+        let rule: RecurrenceRule = rule.parse().unwrap();
+        let except: Vec<NaiveDate> = except.iter().map(|d| date(d)).collect();
+
+        rule.occurrences(date(start), &except, date(start), date(until))
+            .iter()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn weekly_and_monthly_rules_expand() {
+        assert_eq!(
+            dates("FREQ=WEEKLY;BYDAY=FR", "2023-03-03", &[], "2023-03-24"),
+            vec!["2023-03-03", "2023-03-10", "2023-03-17", "2023-03-24"]
+        );
+        assert_eq!(
+            dates(
+                "RRULE:FREQ=MONTHLY;BYDAY=1SU",
+                "2023-03-05",
+                &[],
+                "2023-06-30"
+            ),
+            vec!["2023-03-05", "2023-04-02", "2023-05-07", "2023-06-04"]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=MONTHLY;BYMONTHDAY=-1",
+                "2023-01-31",
+                &[],
+                "2023-04-30"
+            ),
+            vec!["2023-01-31", "2023-02-28", "2023-03-31", "2023-04-30"]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=YEARLY;BYMONTH=12;BYMONTHDAY=24",
+                "2023-12-24",
+                &[],
+                "2025-12-31"
+            ),
+            vec!["2023-12-24", "2024-12-24", "2025-12-24"]
+        );
+    }
+
+    #[test]
+    fn count_interval_and_exceptions_are_applied() {
+        assert_eq!(
+            dates(
+                "FREQ=WEEKLY;INTERVAL=2;COUNT=3",
+                "2023-03-03",
+                &["2023-03-17"],
+                "2023-12-31"
+            ),
+            vec!["2023-03-03", "2023-03-31"]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=DAILY;UNTIL=20230305T000000Z",
+                "2023-03-03",
+                &[],
+                "2023-12-31"
+            ),
+            vec!["2023-03-03", "2023-03-04", "2023-03-05"]
+        );
+    }
+
+    #[test]
+    fn unsupported_rules_are_rejected() {
+        assert!("FREQ=HOURLY".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=WEEKLY;BYSETPOS=1".parse::<RecurrenceRule>().is_err());
+        assert!("BYDAY=FR".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=WEEKLY;BYDAY=1FR".parse::<RecurrenceRule>().is_err());
+
+        let raw = "name: Pizza\ntime: 2023-03-03\nrecurrence: FREQ=SOMETIMES";
+        assert!(serde_yaml::from_str::<RawEvent>(raw).is_err());
+    }
+
+    #[test]
+    fn occurrences_have_stable_ids() {
+        let raw = r#"name: Pizza
+time: 2023-03-03 18:00
+recurrence:
+  rule: FREQ=WEEKLY;BYDAY=FR
+  except:
+    - 2023-03-10"#;
+
+        let mut raw_event: RawEvent = serde_yaml::from_str(raw).unwrap();
+        raw_event.slug = "pizza-friday".into();
+        let event: Event = raw_event.into();
+
+        let expand = || expand(vec![event.clone()], date("2023-03-04"), date("2023-03-31"));
+        let occurrences = expand();
+
+        assert_eq!(occurrences.len(), 3);
+        assert_eq!(occurrences[0].time, date("2023-03-17"));
+        assert_eq!(occurrences[0].slug, "pizza-friday-2023-03-17");
+        assert_eq!(occurrences[0].series_id, Some(event.id));
+        assert_eq!(occurrences[0].start_time, event.start_time);
+        assert_eq!(
+            occurrences[0].id,
+            occurrence_id(event.id, date("2023-03-17"))
+        );
+        assert_eq!(
+            occurrences.iter().map(|e| e.id).collect::<Vec<_>>(),
+            expand().iter().map(|e| e.id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn huge_intervals_are_rejected_and_never_overflow() {
+        assert!("FREQ=DAILY;INTERVAL=100000000"
+            .parse::<RecurrenceRule>()
+            .is_err());
+
+        // Series close to the end of the calendar stop there.
+        let start = NaiveDate::from_ymd_opt(262_142, 12, 1).unwrap();
+        for rule in [
+            "FREQ=DAILY;INTERVAL=1000",
+            "FREQ=WEEKLY;INTERVAL=1000;BYDAY=MO,SU",
+            "FREQ=MONTHLY;INTERVAL=1000",
+            "FREQ=YEARLY;INTERVAL=1000",
+        ] {
+            let rule: RecurrenceRule = rule.parse().unwrap();
+            let dates = rule.occurrences(start, &[], start, NaiveDate::MAX);
+            assert_eq!(dates.first(), Some(&start));
+        }
+    }
+}
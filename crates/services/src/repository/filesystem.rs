@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{Event, ParseDiagnostic, Recipe, SyncStatus};
+use notify::{RecursiveMode, Watcher};
+use tokio::sync::watch;
+
+use crate::ContentConfig;
+
+use super::{event_file_path, ContentState, EventFileChange, EventRepository};
+
+/// Editors tend to touch a file several times when saving it, so changes are
+/// collected for a short while before anything is reloaded.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Content read from a directory on the local filesystem, which is watched for
+/// changes.
+#[derive(Clone)]
+pub struct FileSystemEventRepository {
+    config: ContentConfig,
+    state: Arc<ContentState>,
+}
+
+impl FileSystemEventRepository {
+    pub fn new(config: ContentConfig) -> Self {
+        Self {
+            config,
+            state: Default::default(),
+        }
+    }
+
+    /// Watches the content directories, and reloads the files that change.
+    async fn watch(self) -> eyre::Result<()> {
+        let root = self.config.path.clone();
+        let events_dir = root.join(&self.config.events_dir);
+        let recipes_dir = root.join(self.config.recipes_dir());
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.blocking_send(res);
+        })?;
+
+        watcher.watch(&events_dir, RecursiveMode::NonRecursive)?;
+        if recipes_dir.is_dir() {
+            watcher.watch(&recipes_dir, RecursiveMode::NonRecursive)?;
+        }
+
+        tracing::info!(
+            path = root.display().to_string(),
+            "watching content for changes"
+        );
+
+        while let Some(res) = rx.recv().await {
+            let mut changed = HashSet::<PathBuf>::new();
+            collect(&mut changed, res);
+
+            tokio::time::sleep(DEBOUNCE).await;
+            while let Ok(res) = rx.try_recv() {
+                collect(&mut changed, res);
+            }
+
+            if changed.iter().any(|path| path.starts_with(&recipes_dir)) {
+                // Events link to recipes, so a changed recipe reloads everything.
+                tracing::info!("recipes changed, reloading content");
+                if let Err(e) = self.state.load(&self.config, &root).await {
+                    tracing::warn!(error = e.to_string(), "failed to reload content");
+                }
+                continue;
+            }
+
+            for path in changed.iter().filter(|path| path.starts_with(&events_dir)) {
+                tracing::info!(path = path.display().to_string(), "reloading article");
+                if let Err(e) = self.state.reload_event(&root, path).await {
+                    tracing::warn!(error = e.to_string(), "failed to reload article");
+                }
+            }
+        }
+
+        // The watcher is dropped together with the sender, so this only happens
+        // if the watcher itself went away.
+        drop(watcher);
+        eyre::bail!("stopped receiving changes from the content directory")
+    }
+}
+
+#[async_trait]
+impl EventRepository for FileSystemEventRepository {
+    async fn bootstrap(&self) -> eyre::Result<()> {
+        tracing::info!(
+            path = self.config.path.display().to_string(),
+            "reading content from the filesystem"
+        );
+        self.state.load(&self.config, &self.config.path).await?;
+        self.state.record_success(None).await;
+
+        let repository = self.clone();
+        tokio::task::spawn(async move {
+            if let Err(e) = repository.watch().await {
+                tracing::error!(error = e.to_string(), "stopped watching content");
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn list_events(&self) -> eyre::Result<Vec<Event>> {
+        Ok(self.state.events.read().await.clone())
+    }
+
+    async fn list_recipes(&self) -> eyre::Result<Vec<Recipe>> {
+        Ok(self.state.recipes.read().await.clone())
+    }
+
+    async fn get_diagnostics(&self) -> eyre::Result<Vec<ParseDiagnostic>> {
+        Ok(self.state.diagnostics.read().await.clone())
+    }
+
+    async fn get_sync_status(&self) -> eyre::Result<SyncStatus> {
+        Ok(self.state.status.read().await.clone())
+    }
+
+    async fn list_first_seen(&self) -> eyre::Result<HashMap<uuid::Uuid, DateTime<Utc>>> {
+        Ok(self.state.first_seen.read().await.clone())
+    }
+
+    fn changes(&self) -> Option<watch::Receiver<u64>> {
+        Some(self.state.revision.subscribe())
+    }
+
+    async fn read_event_file(&self, slug: &str) -> eyre::Result<Option<String>> {
+        self.state.read_event_file(&self.config, slug).await
+    }
+
+    /// The file is written in place. Nothing is committed, even if the content
+    /// happens to be in a git repository.
+    async fn write_event_file(&self, change: EventFileChange) -> eyre::Result<()> {
+        let root = &self.config.path;
+        let path = event_file_path(&self.config, root, &change.slug)?;
+
+        match &change.contents {
+            Some(contents) => tokio::fs::write(&path, contents).await?,
+            None => tokio::fs::remove_file(&path).await?,
+        }
+        tracing::info!(
+            path = path.display().to_string(),
+            author = change.author,
+            "{}",
+            change.message
+        );
+
+        self.state.reload_event(root, &path).await
+    }
+}
+
+fn collect(changed: &mut HashSet<PathBuf>, res: notify::Result<notify::Event>) {
+    match res {
+        Ok(event) if !event.kind.is_access() => changed.extend(event.paths),
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = e.to_string(), "error while watching content"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::{ContentConfig, EventFileChange, EventRepository};
+
+    use super::FileSystemEventRepository;
+
+    #[tokio::test]
+    async fn filesystem_content_is_loaded_and_reloaded_per_file() {
+        let root = std::env::temp_dir().join(format!("bitebuds-{}", uuid::Uuid::new_v4()));
+        let events_dir = root.join("events");
+        tokio::fs::create_dir_all(&events_dir).await.unwrap();
+        tokio::fs::write(
+            events_dir.join("2023-03-06-oksesteg.md"),
+            "---\nname: Oksesteg\ntime: 2023-03-06\n---\n",
+        )
+        .await
+        .unwrap();
+
+        let repository = FileSystemEventRepository::new(ContentConfig {
+            path: root.clone(),
+            events_dir: PathBuf::from("events"),
+            ..Default::default()
+        });
+        repository.bootstrap().await.unwrap();
+
+        assert_eq!(repository.list_events().await.unwrap().len(), 1);
+
+        let added = events_dir.join("2023-03-07-frikadeller.md");
+        tokio::fs::write(&added, "---\nname: Frikadeller\ntime: 2023-03-07\n---\n")
+            .await
+            .unwrap();
+        repository.state.reload_event(&root, &added).await.unwrap();
+
+        let frikadeller = repository
+            .get_event_by_slug("2023-03-07-frikadeller")
+            .await
+            .unwrap();
+        assert_eq!(frikadeller.map(|e| e.name), Some("Frikadeller".into()));
+
+        tokio::fs::write(&added, "---\nname: [Frikadeller\n---\n")
+            .await
+            .unwrap();
+        repository.state.reload_event(&root, &added).await.unwrap();
+
+        assert_eq!(repository.list_events().await.unwrap().len(), 1);
+        let diagnostics = repository.get_diagnostics().await.unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "events/2023-03-07-frikadeller.md");
+
+        tokio::fs::remove_file(&added).await.unwrap();
+        repository.state.reload_event(&root, &added).await.unwrap();
+
+        assert!(repository.get_diagnostics().await.unwrap().is_empty());
+
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn event_files_can_be_written_and_removed() {
+        let root = std::env::temp_dir().join(format!("bitebuds-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(root.join("events"))
+            .await
+            .unwrap();
+
+        let repository = FileSystemEventRepository::new(ContentConfig {
+            path: root.clone(),
+            events_dir: PathBuf::from("events"),
+            ..Default::default()
+        });
+        repository
+            .state
+            .load(&repository.config, &root)
+            .await
+            .unwrap();
+
+        let change = EventFileChange {
+            slug: "2023-03-07-frikadeller".into(),
+            contents: Some("---\nname: Frikadeller\ntime: 2023-03-07\n---\n".into()),
+            message: "Add Frikadeller".into(),
+            author: "Kasper".into(),
+            base_commit: None,
+        };
+        repository.write_event_file(change.clone()).await.unwrap();
+
+        assert_eq!(
+            repository
+                .read_event_file("2023-03-07-frikadeller")
+                .await
+                .unwrap(),
+            change.contents
+        );
+        assert_eq!(repository.list_events().await.unwrap().len(), 1);
+
+        assert!(repository
+            .write_event_file(EventFileChange {
+                slug: "../escape".into(),
+                ..change.clone()
+            })
+            .await
+            .is_err());
+
+        repository
+            .write_event_file(EventFileChange {
+                contents: None,
+                ..change
+            })
+            .await
+            .unwrap();
+        assert!(repository.list_events().await.unwrap().is_empty());
+
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+}
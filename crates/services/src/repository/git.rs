@@ -0,0 +1,693 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{Event, ParseDiagnostic, Recipe, SyncStatus};
+use gitevents_sdk::events::EventResponse;
+use tokio::sync::{watch, Mutex};
+
+use crate::ContentConfig;
+
+use super::{event_file_path, ContentState, EventFileChange, EventRepository};
+
+/// Committer of changes made on the site. The author of the commit is the
+/// author who made the change.
+const COMMITTER_EMAIL: &str = "bitebuds@localhost";
+
+/// Exponential backoff used between failed syncs.
+#[derive(Clone, Debug)]
+pub(crate) struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Backoff {
+    pub fn delay(&self, consecutive_failures: u32) -> Duration {
+        if consecutive_failures == 0 {
+            return Duration::ZERO;
+        }
+
+        let factor = 2u32.saturating_pow(consecutive_failures - 1);
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(5),
+            max: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+/// Content synced from a git repository. The repository is polled in the
+/// background, and failed syncs are retried with a backoff.
+#[derive(Clone)]
+pub struct GitEventRepository {
+    config: ContentConfig,
+    repo_url: String,
+    state: Arc<ContentState>,
+    backoff: Backoff,
+    /// Changes from the site are made one at a time, in a clone of their own.
+    edits: Arc<Mutex<()>>,
+    /// The last commit pushed from the site, until the subscription has
+    /// fetched it.
+    published: Arc<Mutex<Option<String>>>,
+}
+
+impl GitEventRepository {
+    pub fn new(config: ContentConfig, repo_url: String) -> Self {
+        Self {
+            config,
+            repo_url,
+            state: Default::default(),
+            backoff: Default::default(),
+            edits: Default::default(),
+            published: Default::default(),
+        }
+    }
+
+    /// Keeps the git subscription alive. Whenever it stops, whether by
+    /// returning an error or by panicking, it is started again after a backoff.
+    async fn supervise(self) {
+        loop {
+            let subscription = tokio::task::spawn(self.clone().subscribe());
+
+            let error = match subscription.await {
+                Ok(Ok(())) => eyre::eyre!("git sync stopped"),
+                Ok(Err(e)) => e,
+                Err(e) => eyre::eyre!("git sync panicked: {}", e),
+            };
+
+            let delay = self.state.record_failure(&error, &self.backoff).await;
+            tracing::error!(
+                error = error.to_string(),
+                retry_in_secs = delay.as_secs(),
+                "git sync failed, restarting"
+            );
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn subscribe(self) -> eyre::Result<()> {
+        tracing::info!(repo_url = self.repo_url, "subscribing to repo");
+
+        gitevents_sdk::builder::Builder::new()
+            .set_generic_git_url(self.repo_url.clone())
+            .set_scheduler_opts(&gitevents_sdk::cron::SchedulerOpts {
+                duration: self.config.sync_interval,
+            })
+            .action(move |req| {
+                let repository = self.clone();
+
+                async move {
+                    let next_attempt_at = repository.state.status.read().await.next_attempt_at;
+                    if let Some(next_attempt_at) = next_attempt_at {
+                        if chrono::Utc::now() < next_attempt_at {
+                            tracing::debug!("backing off, skipping sync");
+                            return Ok(EventResponse {});
+                        }
+                    }
+
+                    tracing::info!("updating articles");
+                    match repository.sync_checkout(&req.git.path).await {
+                        Ok(commit) => repository.state.record_success(commit).await,
+                        Err(e) => {
+                            let delay = repository
+                                .state
+                                .record_failure(&e, &repository.backoff)
+                                .await;
+                            tracing::warn!(
+                                error = e.to_string(),
+                                retry_in_secs = delay.as_secs(),
+                                "failed to update articles"
+                            );
+                        }
+                    }
+
+                    Ok(EventResponse {})
+                }
+            })
+            .execute()
+            .await
+    }
+
+    /// Loads the content of a checkout, returning the commit it was loaded
+    /// from. A checkout which hasn't fetched the last change from the site yet
+    /// is skipped, as it would hide that change until the next sync.
+    async fn sync_checkout(&self, repo_path: &Path) -> eyre::Result<Option<String>> {
+        let root = match &self.config.branch {
+            Some(branch) => checkout_branch(repo_path, branch).await?,
+            None => repo_path.to_path_buf(),
+        };
+
+        // Held while loading, so a change published meanwhile isn't replaced
+        // by the older content.
+        let mut published = self.published.lock().await;
+        if let Some(commit) = published.as_deref() {
+            let caught_up = is_ancestor(&root, commit, "HEAD").await;
+            // A commit which has been fetched without being behind the
+            // checkout was dropped from the branch, and there is nothing to
+            // wait for.
+            let fetched = git(&root, &["cat-file", "-e", &format!("{commit}^{{commit}}")])
+                .await
+                .is_ok();
+            if !caught_up && (!fetched || is_ancestor(&root, "HEAD", commit).await) {
+                tracing::debug!(commit, "waiting for the last change to be fetched");
+                return Ok(None);
+            }
+        }
+        *published = None;
+
+        self.state.load(&self.config, &root).await?;
+
+        Ok(current_commit(&root).await)
+    }
+
+    /// The clone changes from the site are committed in. It is kept apart
+    /// from the checkout of the subscription, which may be replaced at any
+    /// time.
+    async fn edit_checkout(&self) -> eyre::Result<PathBuf> {
+        let checkout = std::env::temp_dir().join(format!(
+            "bitebuds-edits-{}",
+            uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_URL, self.repo_url.as_bytes())
+        ));
+
+        if !tokio::fs::try_exists(checkout.join(".git")).await? {
+            git(
+                &std::env::temp_dir(),
+                &["clone", &self.repo_url, &checkout.display().to_string()],
+            )
+            .await?;
+        }
+
+        Ok(checkout)
+    }
+
+    /// Commits the change on top of the latest commit of the branch and pushes
+    /// it. The change is refused if the file was changed by someone else after
+    /// the author's base commit. Without a base commit, only new files can be
+    /// written.
+    async fn publish(&self, change: EventFileChange) -> eyre::Result<Option<String>> {
+        let _edit = self.edits.lock().await;
+        let checkout = self.edit_checkout().await?;
+
+        let branch = match &self.config.branch {
+            Some(branch) => branch.clone(),
+            None => default_branch(&checkout).await?,
+        };
+        git(&checkout, &["fetch", "origin", &branch]).await?;
+        let remote = git(&checkout, &["rev-parse", "FETCH_HEAD"]).await?;
+
+        let path = event_file_path(&self.config, &checkout, &change.slug)?;
+        let relative_path = path
+            .strip_prefix(&checkout)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+
+        git(&checkout, &["checkout", "--force", "--detach", &remote]).await?;
+        match change.base_commit.as_deref() {
+            None if change.contents.is_none() => eyre::bail!(
+                "{relative_path} can only be removed with the commit the editor was opened from, reload and try again"
+            ),
+            None if path.exists() => {
+                eyre::bail!("{relative_path} already exists, reload and try again")
+            }
+            None => {}
+            Some(base_commit) if base_commit == remote => {}
+            Some(base_commit) => {
+                if git(
+                    &checkout,
+                    &[
+                        "rev-parse",
+                        "--verify",
+                        "--quiet",
+                        &format!("{base_commit}^{{commit}}"),
+                    ],
+                )
+                .await
+                .is_err()
+                {
+                    eyre::bail!(
+                        "the editor was opened from a version the content repository doesn't know, reload and try again"
+                    );
+                }
+
+                let changed = git(
+                    &checkout,
+                    &[
+                        "diff",
+                        "--name-only",
+                        "--end-of-options",
+                        base_commit,
+                        &remote,
+                        "--",
+                        &relative_path,
+                    ],
+                )
+                .await?;
+                if !changed.is_empty() {
+                    eyre::bail!(
+                        "{relative_path} was changed in the content repository after you started editing, reload to see the changes"
+                    );
+                }
+            }
+        }
+
+        match &change.contents {
+            Some(contents) => {
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(&path, contents).await?;
+            }
+            None => tokio::fs::remove_file(&path).await?,
+        }
+
+        git(&checkout, &["add", "--all", "--", &relative_path]).await?;
+        git(
+            &checkout,
+            &[
+                "-c",
+                &format!("user.name={}", change.author),
+                "-c",
+                &format!("user.email={COMMITTER_EMAIL}"),
+                "commit",
+                "--message",
+                &change.message,
+            ],
+        )
+        .await?;
+
+        if let Err(e) = git(
+            &checkout,
+            &["push", "origin", &format!("HEAD:refs/heads/{branch}")],
+        )
+        .await
+        {
+            tracing::warn!(error = e.to_string(), "could not push change");
+            eyre::bail!("the content repository changed while saving, please try again");
+        }
+
+        let commit = current_commit(&checkout).await;
+        let mut published = self.published.lock().await;
+        *published = commit.clone();
+        self.state.load(&self.config, &checkout).await?;
+
+        Ok(commit)
+    }
+}
+
+#[async_trait]
+impl EventRepository for GitEventRepository {
+    async fn bootstrap(&self) -> eyre::Result<()> {
+        tokio::task::spawn(self.clone().supervise());
+
+        Ok(())
+    }
+
+    async fn list_events(&self) -> eyre::Result<Vec<Event>> {
+        Ok(self.state.events.read().await.clone())
+    }
+
+    async fn list_recipes(&self) -> eyre::Result<Vec<Recipe>> {
+        Ok(self.state.recipes.read().await.clone())
+    }
+
+    async fn get_diagnostics(&self) -> eyre::Result<Vec<ParseDiagnostic>> {
+        Ok(self.state.diagnostics.read().await.clone())
+    }
+
+    async fn get_sync_status(&self) -> eyre::Result<SyncStatus> {
+        Ok(self.state.status.read().await.clone())
+    }
+
+    async fn list_first_seen(&self) -> eyre::Result<HashMap<uuid::Uuid, DateTime<Utc>>> {
+        Ok(self.state.first_seen.read().await.clone())
+    }
+
+    fn changes(&self) -> Option<watch::Receiver<u64>> {
+        Some(self.state.revision.subscribe())
+    }
+
+    async fn read_event_file(&self, slug: &str) -> eyre::Result<Option<String>> {
+        self.state.read_event_file(&self.config, slug).await
+    }
+
+    async fn write_event_file(&self, change: EventFileChange) -> eyre::Result<()> {
+        tracing::info!(
+            slug = change.slug,
+            author = change.author,
+            "publishing change"
+        );
+
+        let commit = self.publish(change).await?;
+        self.state.record_success(commit).await;
+
+        Ok(())
+    }
+}
+
+/// The subscription follows the default branch of the repository. Other
+/// branches are fetched into a worktree of their own, which is recreated on
+/// every sync.
+async fn checkout_branch(repo_path: &Path, branch: &str) -> eyre::Result<PathBuf> {
+    let worktree = std::env::temp_dir().join(format!(
+        "bitebuds-content-{}",
+        branch.replace(|c: char| !c.is_ascii_alphanumeric(), "-")
+    ));
+
+    git(repo_path, &["fetch", "origin", branch]).await?;
+    if tokio::fs::try_exists(&worktree).await? {
+        git(
+            repo_path,
+            &[
+                "worktree",
+                "remove",
+                "--force",
+                &worktree.display().to_string(),
+            ],
+        )
+        .await?;
+    }
+    git(
+        repo_path,
+        &[
+            "worktree",
+            "add",
+            "--force",
+            "--detach",
+            &worktree.display().to_string(),
+            "FETCH_HEAD",
+        ],
+    )
+    .await?;
+
+    Ok(worktree)
+}
+
+/// The branch `origin/HEAD` points to, such as `main`.
+async fn default_branch(repo_path: &Path) -> eyre::Result<String> {
+    let head = git(repo_path, &["rev-parse", "--abbrev-ref", "origin/HEAD"]).await?;
+
+    head.strip_prefix("origin/")
+        .map(|branch| branch.to_string())
+        .ok_or_else(|| eyre::eyre!("could not find the default branch of the content repository"))
+}
+
+/// Whether `ancestor` is `commit` or comes before it. Commits which haven't
+/// been fetched come before nothing.
+async fn is_ancestor(repo_path: &Path, ancestor: &str, commit: &str) -> bool {
+    git(
+        repo_path,
+        &["merge-base", "--is-ancestor", ancestor, commit],
+    )
+    .await
+    .is_ok()
+}
+
+async fn current_commit(repo_path: &Path) -> Option<String> {
+    match git(repo_path, &["rev-parse", "HEAD"]).await {
+        Ok(commit) => Some(commit),
+        Err(e) => {
+            tracing::warn!(
+                error = e.to_string(),
+                "could not resolve commit of content repository"
+            );
+            None
+        }
+    }
+}
+
+async fn git(repo_path: &Path, args: &[&str]) -> eyre::Result<String> {
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        eyre::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    use crate::{ContentConfig, EventFileChange, EventRepository};
+
+    use super::{git, Backoff, GitEventRepository};
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_max() {
+        let backoff = Backoff {
+            initial: Duration::from_secs(5),
+            max: Duration::from_secs(60),
+        };
+
+        assert_eq!(backoff.delay(0), Duration::ZERO);
+        assert_eq!(backoff.delay(1), Duration::from_secs(5));
+        assert_eq!(backoff.delay(2), Duration::from_secs(10));
+        assert_eq!(backoff.delay(4), Duration::from_secs(40));
+        assert_eq!(backoff.delay(5), Duration::from_secs(60));
+        assert_eq!(backoff.delay(100), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn status_tracks_failures_until_success() {
+        let repository = GitEventRepository::new(Default::default(), "unused".into());
+        let error = eyre::eyre!("repo unreachable");
+
+        for _ in 0..2 {
+            repository
+                .state
+                .record_failure(&error, &repository.backoff)
+                .await;
+        }
+
+        let status = repository.get_sync_status().await.unwrap();
+        assert_eq!(status.consecutive_failures, 2);
+        assert_eq!(status.last_error.as_deref(), Some("repo unreachable"));
+        assert!(status.next_attempt_at.is_some());
+
+        repository.state.record_success(Some("abc123".into())).await;
+
+        let status = repository.get_sync_status().await.unwrap();
+        assert_eq!(status.consecutive_failures, 0);
+        assert_eq!(status.last_commit.as_deref(), Some("abc123"));
+        assert!(status.last_success_at.is_some());
+        assert!(status.next_attempt_at.is_none());
+    }
+
+    async fn commit_file(work: &Path, name: &str, contents: &str) {
+        tokio::fs::write(work.join("events").join(name), contents)
+            .await
+            .unwrap();
+        git(work, &["add", "--all"]).await.unwrap();
+        git(
+            work,
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@localhost",
+                "commit",
+                "--message",
+                name,
+            ],
+        )
+        .await
+        .unwrap();
+        git(work, &["push", "origin", "HEAD:main"]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn changes_are_refused_if_the_file_changed_since_the_base_commit() {
+        let dir = std::env::temp_dir().join(format!("bitebuds-{}", uuid::Uuid::new_v4()));
+        let origin = dir.join("origin.git");
+        let work = dir.join("work");
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        git(
+            &dir,
+            &["init", "--bare", "--initial-branch=main", "origin.git"],
+        )
+        .await
+        .unwrap();
+        git(&dir, &["clone", "origin.git", "work"]).await.unwrap();
+        tokio::fs::create_dir_all(work.join("events"))
+            .await
+            .unwrap();
+        commit_file(
+            &work,
+            "oksesteg.md",
+            "---\nname: Oksesteg\ntime: 2023-03-06\n---\n",
+        )
+        .await;
+        let base_commit = git(&work, &["rev-parse", "HEAD"]).await.unwrap();
+
+        let repository = GitEventRepository::new(
+            ContentConfig {
+                branch: Some("main".into()),
+                events_dir: PathBuf::from("events"),
+                ..Default::default()
+            },
+            origin.display().to_string(),
+        );
+
+        let change = EventFileChange {
+            slug: "frikadeller".into(),
+            contents: Some("---\nname: Frikadeller\ntime: 2023-03-07\n---\n".into()),
+            message: "Add Frikadeller".into(),
+            author: "Kasper".into(),
+            base_commit: Some(base_commit.clone()),
+        };
+        repository.write_event_file(change.clone()).await.unwrap();
+
+        assert_eq!(repository.list_events().await.unwrap().len(), 2);
+        let pushed = git(&origin, &["log", "-1", "--format=%an: %s", "main"])
+            .await
+            .unwrap();
+        assert_eq!(pushed, "Kasper: Add Frikadeller");
+
+        git(&work, &["pull", "origin", "main"]).await.unwrap();
+        commit_file(
+            &work,
+            "oksesteg.md",
+            "---\nname: Oksesteg med sovs\ntime: 2023-03-06\n---\n",
+        )
+        .await;
+
+        let conflict = repository
+            .write_event_file(EventFileChange {
+                slug: "oksesteg".into(),
+                base_commit: Some(base_commit),
+                ..change.clone()
+            })
+            .await;
+        assert!(conflict.is_err());
+
+        for base_commit in [None, Some("0".repeat(40))] {
+            let unknown = repository
+                .write_event_file(EventFileChange {
+                    slug: "oksesteg".into(),
+                    base_commit,
+                    ..change.clone()
+                })
+                .await;
+            assert!(unknown.is_err());
+        }
+
+        let removed = repository
+            .write_event_file(EventFileChange {
+                slug: "oksesteg".into(),
+                contents: None,
+                base_commit: None,
+                ..change.clone()
+            })
+            .await;
+        assert!(removed
+            .unwrap_err()
+            .to_string()
+            .contains("can only be removed"));
+
+        // Changes to other files since the base commit are fine.
+        let last_commit = repository.get_sync_status().await.unwrap().last_commit;
+        repository
+            .write_event_file(EventFileChange {
+                contents: None,
+                message: "Remove Frikadeller".into(),
+                base_commit: last_commit,
+                ..change
+            })
+            .await
+            .unwrap();
+
+        let events = repository.list_events().await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "Oksesteg med sovs");
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+        tokio::fs::remove_dir_all(repository.edit_checkout().await.unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn syncs_behind_a_published_change_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("bitebuds-{}", uuid::Uuid::new_v4()));
+        let origin = dir.join("origin.git");
+        let work = dir.join("work");
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        git(
+            &dir,
+            &["init", "--bare", "--initial-branch=main", "origin.git"],
+        )
+        .await
+        .unwrap();
+        git(&dir, &["clone", "origin.git", "work"]).await.unwrap();
+        tokio::fs::create_dir_all(work.join("events"))
+            .await
+            .unwrap();
+        commit_file(
+            &work,
+            "oksesteg.md",
+            "---\nname: Oksesteg\ntime: 2023-03-06\n---\n",
+        )
+        .await;
+
+        // The subscription follows the checkout of the default branch as it is.
+        let repository = GitEventRepository::new(
+            ContentConfig {
+                events_dir: PathBuf::from("events"),
+                ..Default::default()
+            },
+            origin.display().to_string(),
+        );
+        let synced = repository.sync_checkout(&work).await.unwrap();
+        assert!(synced.is_some());
+        assert_eq!(repository.list_events().await.unwrap().len(), 1);
+
+        repository
+            .write_event_file(EventFileChange {
+                slug: "frikadeller".into(),
+                contents: Some("---\nname: Frikadeller\ntime: 2023-03-07\n---\n".into()),
+                message: "Add Frikadeller".into(),
+                author: "Kasper".into(),
+                base_commit: synced,
+            })
+            .await
+            .unwrap();
+        let published = repository.get_sync_status().await.unwrap().last_commit;
+
+        assert_eq!(repository.sync_checkout(&work).await.unwrap(), None);
+        assert_eq!(repository.list_events().await.unwrap().len(), 2);
+
+        git(&work, &["fetch", "origin", "main"]).await.unwrap();
+        assert_eq!(repository.sync_checkout(&work).await.unwrap(), None);
+
+        git(&work, &["merge", "--ff-only", "FETCH_HEAD"])
+            .await
+            .unwrap();
+        assert_eq!(repository.sync_checkout(&work).await.unwrap(), published);
+        assert_eq!(repository.list_events().await.unwrap().len(), 2);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+        tokio::fs::remove_dir_all(repository.edit_checkout().await.unwrap())
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use domain::{Event, Recipe};
+use tokio::sync::RwLock;
+
+use super::EventRepository;
+
+/// Content kept in memory only, handy for tests and for running the site
+/// without any content repository.
+#[derive(Clone, Default)]
+pub struct InMemoryEventRepository {
+    events: Arc<RwLock<Vec<Event>>>,
+    recipes: Arc<RwLock<Vec<Recipe>>>,
+}
+
+impl InMemoryEventRepository {
+    pub fn new(events: Vec<Event>, recipes: Vec<Recipe>) -> Self {
+        Self {
+            events: Arc::new(RwLock::new(events)),
+            recipes: Arc::new(RwLock::new(recipes)),
+        }
+    }
+
+    /// Adds the event, replacing any event with the same id.
+    pub async fn put_event(&self, event: Event) {
+        let mut events = self.events.write().await;
+        events.retain(|e| e.id != event.id);
+        events.push(event);
+    }
+
+    /// Adds the recipe, replacing any recipe with the same id.
+    pub async fn put_recipe(&self, recipe: Recipe) {
+        let mut recipes = self.recipes.write().await;
+        recipes.retain(|r| r.id != recipe.id);
+        recipes.push(recipe);
+    }
+}
+
+#[async_trait]
+impl EventRepository for InMemoryEventRepository {
+    async fn bootstrap(&self) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    async fn list_events(&self) -> eyre::Result<Vec<Event>> {
+        Ok(self.events.read().await.clone())
+    }
+
+    async fn list_recipes(&self) -> eyre::Result<Vec<Recipe>> {
+        Ok(self.recipes.read().await.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use domain::Event;
+
+    use crate::{EventRepository, RawEvent};
+
+    use super::InMemoryEventRepository;
+
+    #[tokio::test]
+    async fn events_can_be_looked_up_by_id_and_slug() {
+        let mut raw_event: RawEvent =
+            serde_yaml::from_str("name: Frikadeller\ntime: 2023-03-07").unwrap();
+        raw_event.slug = "2023-03-07-frikadeller".into();
+        let event: Event = raw_event.into();
+
+        let repository = InMemoryEventRepository::default();
+        repository.put_event(event.clone()).await;
+        repository.put_event(event.clone()).await;
+
+        assert_eq!(repository.list_events().await.unwrap().len(), 1);
+        assert_eq!(
+            repository.get_event(event.id).await.unwrap(),
+            Some(event.clone())
+        );
+        assert_eq!(
+            repository
+                .get_event_by_slug("2023-03-07-frikadeller")
+                .await
+                .unwrap()
+                .map(|e| e.id),
+            Some(event.id)
+        );
+        assert!(repository.get_recipe(event.id).await.unwrap().is_none());
+    }
+}
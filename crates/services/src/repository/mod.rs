@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{Event, ParseDiagnostic, Recipe, SyncStatus};
+use tokio::sync::{watch, RwLock};
+
+use crate::front_matter::{read_document, relative_to, slug_from_path};
+use crate::recipes::{fetch_recipes, link_recipes};
+use crate::{event_from_document, fetch_events, ContentConfig, RawEvent};
+
+mod filesystem;
+mod git;
+mod memory;
+mod sqlite;
+
+pub use filesystem::FileSystemEventRepository;
+pub use git::GitEventRepository;
+pub use memory::InMemoryEventRepository;
+pub use sqlite::SqliteEventRepository;
+
+use git::Backoff;
+
+/// Where events and recipes come from. The web layer only talks to this trait
+/// through [`crate::EventStore`], so backends can be swapped without touching
+/// it.
+#[async_trait]
+pub trait EventRepository: Send + Sync {
+    /// Loads the content, and keeps it up to date in the background if the
+    /// backend supports it.
+    async fn bootstrap(&self) -> eyre::Result<()>;
+
+    async fn list_events(&self) -> eyre::Result<Vec<Event>>;
+
+    async fn get_event(&self, event_id: uuid::Uuid) -> eyre::Result<Option<Event>> {
+        let events = self.list_events().await?;
+
+        Ok(events.into_iter().find(|e| e.id == event_id))
+    }
+
+    async fn get_event_by_slug(&self, slug: &str) -> eyre::Result<Option<Event>> {
+        let events = self.list_events().await?;
+
+        Ok(events.into_iter().find(|e| e.slug == slug))
+    }
+
+    /// Events between `from` and `until`, both included, together with every
+    /// recurring event starting before `until`, as their occurrences may fall
+    /// in between.
+    async fn list_events_between(
+        &self,
+        from: chrono::NaiveDate,
+        until: chrono::NaiveDate,
+    ) -> eyre::Result<Vec<Event>> {
+        let events = self.list_events().await?;
+
+        Ok(events
+            .into_iter()
+            .filter(|e| e.time <= until && (e.time >= from || e.recurrence.is_some()))
+            .collect())
+    }
+
+    async fn list_recipes(&self) -> eyre::Result<Vec<Recipe>>;
+
+    async fn get_recipe(&self, recipe_id: uuid::Uuid) -> eyre::Result<Option<Recipe>> {
+        let recipes = self.list_recipes().await?;
+
+        Ok(recipes.into_iter().find(|r| r.id == recipe_id))
+    }
+
+    async fn get_recipe_by_slug(&self, slug: &str) -> eyre::Result<Option<Recipe>> {
+        let recipes = self.list_recipes().await?;
+
+        Ok(recipes.into_iter().find(|r| r.slug == slug))
+    }
+
+    /// Problems found in the content during the last load.
+    async fn get_diagnostics(&self) -> eyre::Result<Vec<ParseDiagnostic>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_sync_status(&self) -> eyre::Result<SyncStatus> {
+        Ok(SyncStatus::default())
+    }
+
+    /// When each event was first seen, which is when it was added to the plan
+    /// as far as the site knows. Repositories which don't keep track return
+    /// nothing.
+    async fn list_first_seen(&self) -> eyre::Result<HashMap<uuid::Uuid, DateTime<Utc>>> {
+        Ok(HashMap::new())
+    }
+
+    /// A revision which is bumped every time the content changes, starting at
+    /// zero until it is first loaded. Repositories which don't load content of
+    /// their own return `None`.
+    fn changes(&self) -> Option<watch::Receiver<u64>> {
+        None
+    }
+
+    /// The markdown file of an event as it was last loaded, for editing it.
+    async fn read_event_file(&self, _slug: &str) -> eyre::Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Writes or removes the file of an event, publishes the change and
+    /// reloads the content.
+    async fn write_event_file(&self, _change: EventFileChange) -> eyre::Result<()> {
+        eyre::bail!("this content can't be edited from the site")
+    }
+}
+
+/// A change to the file of an event, made by an author on the site.
+#[derive(Clone, Debug)]
+pub struct EventFileChange {
+    pub slug: String,
+    /// The new contents of the file, or `None` to remove it.
+    pub contents: Option<String>,
+    pub message: String,
+    pub author: String,
+    /// The full id of the commit the author started from. If the file has
+    /// changed in the content repository since, the change is refused.
+    pub base_commit: Option<String>,
+}
+
+/// Content loaded from markdown files, shared between a repository and the
+/// background task keeping it up to date.
+pub(crate) struct ContentState {
+    pub events: RwLock<Vec<Event>>,
+    pub recipes: RwLock<Vec<Recipe>>,
+    pub diagnostics: RwLock<Vec<ParseDiagnostic>>,
+    pub status: RwLock<SyncStatus>,
+    /// Only covers the lifetime of the process, see
+    /// [`crate::SqliteEventRepository`] for keeping it across restarts.
+    pub first_seen: RwLock<HashMap<uuid::Uuid, DateTime<Utc>>>,
+    /// Checkout the content was last loaded from.
+    pub root: RwLock<Option<PathBuf>>,
+    pub revision: watch::Sender<u64>,
+}
+
+impl Default for ContentState {
+    fn default() -> Self {
+        Self {
+            events: Default::default(),
+            recipes: Default::default(),
+            diagnostics: Default::default(),
+            status: Default::default(),
+            first_seen: Default::default(),
+            root: Default::default(),
+            revision: watch::channel(0).0,
+        }
+    }
+}
+
+impl ContentState {
+    /// Loads events and recipes from a checkout of the content repository,
+    /// replacing what was loaded before.
+    pub async fn load(&self, config: &ContentConfig, root: &Path) -> eyre::Result<()> {
+        let event_path = root.join(&config.events_dir);
+        let recipe_path = root.join(config.recipes_dir());
+
+        tracing::debug!(path = event_path.display().to_string(), "reading from");
+
+        let (recipes, recipe_diagnostics) = fetch_recipes(recipe_path).await?;
+        let (mut events, event_diagnostics) = fetch_events(event_path).await?;
+        link_recipes(&mut events, &recipes);
+
+        let mut diagnostics = [event_diagnostics, recipe_diagnostics].concat();
+        relative_to(&mut diagnostics, root);
+        if !diagnostics.is_empty() {
+            tracing::warn!(
+                count = diagnostics.len(),
+                "some articles could not be parsed"
+            );
+        }
+
+        let mut e = self.events.write().await;
+        *e = events;
+        let mut r = self.recipes.write().await;
+        *r = recipes;
+        let mut d = self.diagnostics.write().await;
+        *d = diagnostics;
+        mark_seen(&mut *self.first_seen.write().await, &e);
+        *self.root.write().await = Some(root.to_path_buf());
+
+        self.revision.send_modify(|revision| *revision += 1);
+
+        Ok(())
+    }
+
+    /// Reloads a single event file, which may have been changed, added or
+    /// removed.
+    pub async fn reload_event(&self, root: &Path, path: &Path) -> eyre::Result<()> {
+        let slug = slug_from_path(path);
+        let relative_path = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+
+        let document = if tokio::fs::try_exists(path).await? {
+            read_document::<RawEvent>(path.to_path_buf()).await
+        } else {
+            Ok(None)
+        };
+        let recipes = self.recipes.read().await.clone();
+
+        let mut events = self.events.write().await;
+        let mut diagnostics = self.diagnostics.write().await;
+        events.retain(|e| e.slug != slug);
+        diagnostics.retain(|d| d.path != relative_path);
+
+        match document {
+            Ok(Some(document)) => {
+                let mut event = event_from_document(document);
+                link_recipes(std::slice::from_mut(&mut event), &recipes);
+                events.push(event);
+            }
+            Ok(None) => {}
+            Err(mut diagnostic) => {
+                diagnostic.path = relative_path;
+                diagnostics.push(diagnostic);
+            }
+        }
+        mark_seen(&mut *self.first_seen.write().await, &events);
+
+        self.revision.send_modify(|revision| *revision += 1);
+
+        Ok(())
+    }
+
+    pub async fn read_event_file(
+        &self,
+        config: &ContentConfig,
+        slug: &str,
+    ) -> eyre::Result<Option<String>> {
+        let Some(root) = self.root.read().await.clone() else {
+            return Ok(None);
+        };
+        let path = event_file_path(config, &root, slug)?;
+
+        match tokio::fs::read_to_string(path).await {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn record_success(&self, commit: Option<String>) {
+        let mut status = self.status.write().await;
+        status.last_success_at = Some(chrono::Utc::now());
+        if commit.is_some() {
+            status.last_commit = commit;
+        }
+        status.consecutive_failures = 0;
+        status.next_attempt_at = None;
+    }
+
+    /// Records the failure and returns how long to wait before trying again.
+    pub async fn record_failure(&self, error: &eyre::Report, backoff: &Backoff) -> Duration {
+        let mut status = self.status.write().await;
+        let now = chrono::Utc::now();
+        status.last_error = Some(error.to_string());
+        status.last_error_at = Some(now);
+        status.consecutive_failures += 1;
+
+        let delay = backoff.delay(status.consecutive_failures);
+        status.next_attempt_at = chrono::Duration::from_std(delay).ok().map(|d| now + d);
+
+        delay
+    }
+}
+
+/// Where the file of an event lives in a checkout. Slugs come from the site,
+/// so they are checked not to point anywhere else.
+pub(crate) fn event_file_path(
+    config: &ContentConfig,
+    root: &Path,
+    slug: &str,
+) -> eyre::Result<PathBuf> {
+    if slug.is_empty() || slug.starts_with('.') || slug.contains(['/', '\\']) {
+        eyre::bail!("invalid event slug {slug}");
+    }
+
+    Ok(root.join(&config.events_dir).join(format!("{slug}.md")))
+}
+
+/// Records events which haven't been seen before as seen now, and forgets the
+/// ones which are gone.
+fn mark_seen(first_seen: &mut HashMap<uuid::Uuid, DateTime<Utc>>, events: &[Event]) {
+    let now = Utc::now();
+
+    first_seen.retain(|id, _| events.iter().any(|e| e.id == *id));
+    for event in events {
+        first_seen.entry(event.id).or_insert(now);
+    }
+}
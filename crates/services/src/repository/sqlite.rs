@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use domain::{Event, ParseDiagnostic, Recipe, SyncStatus};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+
+use super::{EventFileChange, EventRepository};
+
+/// Content mirrored into a SQLite database, so it is served from disk and
+/// survives restarts. The database is also where data owned by the app itself
+/// lives.
+///
+/// Content is still loaded by a source repository, and copied into the
+/// database whenever the source reports a change.
+#[derive(Clone)]
+pub struct SqliteEventRepository {
+    pool: SqlitePool,
+    source: Arc<dyn EventRepository>,
+}
+
+impl SqliteEventRepository {
+    /// Opens the database at `path`, creating it if needed, and brings its
+    /// schema up to date.
+    pub async fn connect(path: &Path, source: Arc<dyn EventRepository>) -> eyre::Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+
+        sqlx::migrate!().run(&pool).await?;
+
+        Ok(Self { pool, source })
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Copies the content of the source into the database, removing whatever
+    /// is no longer in the source.
+    async fn mirror(&self) -> eyre::Result<()> {
+        let events = self.source.list_events().await?;
+        let recipes = self.source.list_recipes().await?;
+        let first_seen = self.source.list_first_seen().await?;
+        let now = Utc::now();
+
+        let mut tx = self.pool.begin().await?;
+
+        for event in &events {
+            sqlx::query(
+                "INSERT INTO events (id, slug, time, data, first_seen_at) VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT (id) DO UPDATE
+                SET slug = excluded.slug, time = excluded.time, data = excluded.data,
+                first_seen_at = MIN(first_seen_at, excluded.first_seen_at)",
+            )
+            .bind(event.id.to_string())
+            .bind(&event.slug)
+            .bind(event.time.to_string())
+            .bind(serde_json::to_string(event)?)
+            .bind(first_seen.get(&event.id).unwrap_or(&now).timestamp())
+            .execute(&mut tx)
+            .await?;
+        }
+
+        let event_ids: HashSet<String> = events.iter().map(|e| e.id.to_string()).collect();
+        let stored: Vec<(String,)> = sqlx::query_as("SELECT id FROM events")
+            .fetch_all(&mut tx)
+            .await?;
+        for (id,) in stored.into_iter().filter(|(id,)| !event_ids.contains(id)) {
+            sqlx::query("DELETE FROM events WHERE id = ?")
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+
+        for recipe in &recipes {
+            sqlx::query(
+                "INSERT INTO recipes (id, slug, data) VALUES (?, ?, ?)
+                ON CONFLICT (id) DO UPDATE
+                SET slug = excluded.slug, data = excluded.data",
+            )
+            .bind(recipe.id.to_string())
+            .bind(&recipe.slug)
+            .bind(serde_json::to_string(recipe)?)
+            .execute(&mut tx)
+            .await?;
+        }
+
+        let recipe_ids: HashSet<String> = recipes.iter().map(|r| r.id.to_string()).collect();
+        let stored: Vec<(String,)> = sqlx::query_as("SELECT id FROM recipes")
+            .fetch_all(&mut tx)
+            .await?;
+        for (id,) in stored.into_iter().filter(|(id,)| !recipe_ids.contains(id)) {
+            sqlx::query("DELETE FROM recipes WHERE id = ?")
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        tracing::debug!(
+            events = events.len(),
+            recipes = recipes.len(),
+            "mirrored content into the database"
+        );
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventRepository for SqliteEventRepository {
+    async fn bootstrap(&self) -> eyre::Result<()> {
+        self.source.bootstrap().await?;
+
+        let Some(mut changes) = self.source.changes() else {
+            return Ok(());
+        };
+
+        // Until the source has loaded anything, keep serving what was stored
+        // before the restart.
+        if *changes.borrow_and_update() > 0 {
+            self.mirror().await?;
+        }
+
+        let repository = self.clone();
+        tokio::task::spawn(async move {
+            while changes.changed().await.is_ok() {
+                if let Err(e) = repository.mirror().await {
+                    tracing::warn!(error = e.to_string(), "failed to mirror content");
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn list_events(&self) -> eyre::Result<Vec<Event>> {
+        let rows: Vec<(String,)> = sqlx::query_as("SELECT data FROM events ORDER BY time")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|(data,)| Ok(serde_json::from_str(&data)?))
+            .collect()
+    }
+
+    async fn get_event(&self, event_id: uuid::Uuid) -> eyre::Result<Option<Event>> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT data FROM events WHERE id = ?")
+            .bind(event_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|(data,)| Ok(serde_json::from_str(&data)?))
+            .transpose()
+    }
+
+    async fn get_event_by_slug(&self, slug: &str) -> eyre::Result<Option<Event>> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT data FROM events WHERE slug = ?")
+            .bind(slug)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|(data,)| Ok(serde_json::from_str(&data)?))
+            .transpose()
+    }
+
+    async fn list_events_between(
+        &self,
+        from: chrono::NaiveDate,
+        until: chrono::NaiveDate,
+    ) -> eyre::Result<Vec<Event>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT data FROM events
+            WHERE time <= ?
+            AND (time >= ? OR json_extract(data, '$.recurrence') IS NOT NULL)
+            ORDER BY time",
+        )
+        .bind(until.to_string())
+        .bind(from.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(data,)| Ok(serde_json::from_str(&data)?))
+            .collect()
+    }
+
+    async fn list_recipes(&self) -> eyre::Result<Vec<Recipe>> {
+        let rows: Vec<(String,)> = sqlx::query_as("SELECT data FROM recipes")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|(data,)| Ok(serde_json::from_str(&data)?))
+            .collect()
+    }
+
+    async fn get_recipe(&self, recipe_id: uuid::Uuid) -> eyre::Result<Option<Recipe>> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT data FROM recipes WHERE id = ?")
+            .bind(recipe_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|(data,)| Ok(serde_json::from_str(&data)?))
+            .transpose()
+    }
+
+    async fn get_recipe_by_slug(&self, slug: &str) -> eyre::Result<Option<Recipe>> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT data FROM recipes WHERE slug = ?")
+            .bind(slug)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|(data,)| Ok(serde_json::from_str(&data)?))
+            .transpose()
+    }
+
+    async fn get_diagnostics(&self) -> eyre::Result<Vec<ParseDiagnostic>> {
+        self.source.get_diagnostics().await
+    }
+
+    async fn get_sync_status(&self) -> eyre::Result<SyncStatus> {
+        self.source.get_sync_status().await
+    }
+
+    async fn read_event_file(&self, slug: &str) -> eyre::Result<Option<String>> {
+        self.source.read_event_file(slug).await
+    }
+
+    /// The change is mirrored straight away, rather than in the background,
+    /// so it shows up as soon as this returns.
+    async fn write_event_file(&self, change: EventFileChange) -> eyre::Result<()> {
+        self.source.write_event_file(change).await?;
+
+        self.mirror().await
+    }
+
+    /// Kept in the database, so events keep the time they were first seen by
+    /// any run of the site.
+    async fn list_first_seen(&self) -> eyre::Result<HashMap<uuid::Uuid, DateTime<Utc>>> {
+        let rows: Vec<(String, i64)> = sqlx::query_as("SELECT id, first_seen_at FROM events")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|(id, first_seen_at)| {
+                let first_seen_at = Utc
+                    .timestamp_opt(first_seen_at, 0)
+                    .single()
+                    .ok_or_else(|| eyre::eyre!("invalid first seen time {first_seen_at}"))?;
+
+                Ok((id.parse()?, first_seen_at))
+            })
+            .collect()
+    }
+
+    fn changes(&self) -> Option<tokio::sync::watch::Receiver<u64>> {
+        self.source.changes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use chrono::NaiveDate;
+    use domain::Event;
+
+    use crate::{EventRepository, InMemoryEventRepository, RawEvent};
+
+    use super::SqliteEventRepository;
+
+    fn event(slug: &str) -> Event {
+        let mut raw_event: RawEvent =
+            serde_yaml::from_str("name: Frikadeller\ntime: 2023-03-07").unwrap();
+        raw_event.slug = slug.into();
+        raw_event.into()
+    }
+
+    #[tokio::test]
+    async fn content_is_mirrored_and_survives_restarts() {
+        let path = std::env::temp_dir().join(format!("bitebuds-{}.db", uuid::Uuid::new_v4()));
+
+        let source = InMemoryEventRepository::new(
+            vec![event("2023-03-07-frikadeller"), event("2023-03-08-boller")],
+            vec![],
+        );
+        let repository = SqliteEventRepository::connect(&path, Arc::new(source))
+            .await
+            .unwrap();
+        repository.mirror().await.unwrap();
+
+        assert_eq!(repository.list_events().await.unwrap().len(), 2);
+        let first_seen = repository.list_first_seen().await.unwrap();
+        assert_eq!(first_seen.len(), 2);
+
+        // The in memory repository never reports changes, so nothing is
+        // mirrored when bootstrapping and the stored events are kept.
+        let source = InMemoryEventRepository::new(vec![event("2023-03-08-boller")], vec![]);
+        let repository = SqliteEventRepository::connect(&path, Arc::new(source))
+            .await
+            .unwrap();
+        repository.bootstrap().await.unwrap();
+
+        assert_eq!(repository.list_events().await.unwrap().len(), 2);
+
+        repository.mirror().await.unwrap();
+
+        let events = repository.list_events().await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            repository
+                .list_first_seen()
+                .await
+                .unwrap()
+                .get(&events[0].id),
+            first_seen.get(&events[0].id)
+        );
+        assert_eq!(
+            repository
+                .get_event_by_slug("2023-03-08-boller")
+                .await
+                .unwrap(),
+            Some(events[0].clone())
+        );
+        assert!(repository
+            .get_event_by_slug("2023-03-07-frikadeller")
+            .await
+            .unwrap()
+            .is_none());
+
+        let between = repository
+            .list_events_between(
+                NaiveDate::from_ymd_opt(2023, 3, 7).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 3, 31).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(between, events);
+        assert!(repository
+            .list_events_between(
+                NaiveDate::from_ymd_opt(2023, 3, 8).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 3, 31).unwrap(),
+            )
+            .await
+            .unwrap()
+            .is_empty());
+
+        repository.pool().close().await;
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
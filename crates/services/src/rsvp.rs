@@ -0,0 +1,267 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use domain::{Attendance, Rsvp};
+use sqlx::sqlite::SqlitePool;
+use tokio::sync::RwLock;
+
+pub const MAX_NAME_LENGTH: usize = 60;
+pub const MAX_NOTE_LENGTH: usize = 500;
+pub const MAX_HEADCOUNT: u32 = 20;
+
+/// Where answers from guests are kept. Unlike events they are owned by the
+/// site, so they are stored in the database when there is one.
+#[async_trait]
+pub trait RsvpRepository: Send + Sync {
+    /// Answers for the event, oldest first.
+    async fn list_rsvps(&self, event_id: uuid::Uuid) -> eyre::Result<Vec<Rsvp>>;
+
+    /// Stores the answer, replacing an earlier answer for the same event from
+    /// a guest with the same name, ignoring case. Returns the stored answer,
+    /// which keeps the id of the one it replaced.
+    async fn put_rsvp(&self, rsvp: Rsvp) -> eyre::Result<Rsvp>;
+}
+
+/// Answers are lost on restart, used when no database is configured.
+#[derive(Clone, Default)]
+pub struct InMemoryRsvpRepository {
+    rsvps: Arc<RwLock<Vec<Rsvp>>>,
+}
+
+#[async_trait]
+impl RsvpRepository for InMemoryRsvpRepository {
+    async fn list_rsvps(&self, event_id: uuid::Uuid) -> eyre::Result<Vec<Rsvp>> {
+        Ok(self
+            .rsvps
+            .read()
+            .await
+            .iter()
+            .filter(|r| r.event_id == event_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn put_rsvp(&self, mut rsvp: Rsvp) -> eyre::Result<Rsvp> {
+        let mut rsvps = self.rsvps.write().await;
+
+        if let Some(existing) = rsvps
+            .iter()
+            .position(|r| r.event_id == rsvp.event_id && name_key(&r.name) == name_key(&rsvp.name))
+        {
+            rsvp.id = rsvps.remove(existing).id;
+        }
+        rsvps.push(rsvp.clone());
+
+        Ok(rsvp)
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteRsvpRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteRsvpRepository {
+    /// The schema is created by the migrations run in
+    /// [`crate::SqliteEventRepository::connect`].
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+type RsvpRow = (String, String, String, String, i64, Option<String>, i64);
+
+#[async_trait]
+impl RsvpRepository for SqliteRsvpRepository {
+    async fn list_rsvps(&self, event_id: uuid::Uuid) -> eyre::Result<Vec<Rsvp>> {
+        let rows: Vec<RsvpRow> = sqlx::query_as(
+            "SELECT id, event_id, name, attendance, headcount, note, updated_at
+            FROM rsvps WHERE event_id = ? ORDER BY updated_at, name_key",
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(rsvp_from_row).collect()
+    }
+
+    async fn put_rsvp(&self, rsvp: Rsvp) -> eyre::Result<Rsvp> {
+        let (id,): (String,) = sqlx::query_as(
+            "INSERT INTO rsvps (id, event_id, name, name_key, attendance, headcount, note, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (event_id, name_key) DO UPDATE
+            SET name = excluded.name, attendance = excluded.attendance,
+            headcount = excluded.headcount, note = excluded.note, updated_at = excluded.updated_at
+            RETURNING id",
+        )
+        .bind(rsvp.id.to_string())
+        .bind(rsvp.event_id.to_string())
+        .bind(&rsvp.name)
+        .bind(name_key(&rsvp.name))
+        .bind(attendance_to_str(rsvp.attendance))
+        .bind(rsvp.headcount as i64)
+        .bind(&rsvp.note)
+        .bind(rsvp.updated_at.timestamp())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Rsvp {
+            id: id.parse()?,
+            ..rsvp
+        })
+    }
+}
+
+fn rsvp_from_row(
+    (id, event_id, name, attendance, headcount, note, updated_at): RsvpRow,
+) -> eyre::Result<Rsvp> {
+    Ok(Rsvp {
+        id: id.parse()?,
+        event_id: event_id.parse()?,
+        name,
+        attendance: attendance_from_str(&attendance)?,
+        headcount: headcount.try_into()?,
+        note,
+        updated_at: Utc
+            .timestamp_opt(updated_at, 0)
+            .single()
+            .ok_or_else(|| eyre::eyre!("invalid rsvp time {updated_at}"))?,
+    })
+}
+
+fn attendance_to_str(attendance: Attendance) -> &'static str {
+    match attendance {
+        Attendance::Attending => "attending",
+        Attendance::Maybe => "maybe",
+        Attendance::Declined => "declined",
+    }
+}
+
+fn attendance_from_str(attendance: &str) -> eyre::Result<Attendance> {
+    match attendance {
+        "attending" => Ok(Attendance::Attending),
+        "maybe" => Ok(Attendance::Maybe),
+        "declined" => Ok(Attendance::Declined),
+        other => Err(eyre::eyre!("unknown attendance {other}")),
+    }
+}
+
+fn name_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Checks and tidies an answer from a guest before it is stored.
+pub fn validate(
+    name: &str,
+    headcount: u32,
+    note: Option<String>,
+) -> eyre::Result<(String, u32, Option<String>)> {
+    let name = name.trim();
+    if name.is_empty() {
+        eyre::bail!("please tell us your name");
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        eyre::bail!("names can be at most {MAX_NAME_LENGTH} characters");
+    }
+    if headcount == 0 || headcount > MAX_HEADCOUNT {
+        eyre::bail!("the headcount must be between 1 and {MAX_HEADCOUNT}");
+    }
+
+    let note = note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+    if note
+        .as_ref()
+        .is_some_and(|note| note.chars().count() > MAX_NOTE_LENGTH)
+    {
+        eyre::bail!("notes can be at most {MAX_NOTE_LENGTH} characters");
+    }
+
+    Ok((name.to_string(), headcount, note))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use domain::{Attendance, Rsvp};
+
+    use crate::{InMemoryEventRepository, SqliteEventRepository};
+
+    use super::{validate, InMemoryRsvpRepository, RsvpRepository, SqliteRsvpRepository};
+
+    fn rsvp(event_id: uuid::Uuid, name: &str, attendance: Attendance) -> Rsvp {
+        Rsvp {
+            id: uuid::Uuid::new_v4(),
+            event_id,
+            name: name.into(),
+            attendance,
+            headcount: 2,
+            note: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    async fn answers_replace_earlier_answers_by_name(repository: &dyn RsvpRepository) {
+        let event_id = uuid::Uuid::new_v4();
+
+        let first = repository
+            .put_rsvp(rsvp(event_id, "Mormor", Attendance::Maybe))
+            .await
+            .unwrap();
+        repository
+            .put_rsvp(rsvp(event_id, "Morfar", Attendance::Declined))
+            .await
+            .unwrap();
+        let changed = repository
+            .put_rsvp(rsvp(event_id, " mormor", Attendance::Attending))
+            .await
+            .unwrap();
+        repository
+            .put_rsvp(rsvp(uuid::Uuid::new_v4(), "Mormor", Attendance::Attending))
+            .await
+            .unwrap();
+
+        assert_eq!(changed.id, first.id);
+
+        let rsvps = repository.list_rsvps(event_id).await.unwrap();
+        assert_eq!(rsvps.len(), 2);
+        assert!(rsvps
+            .iter()
+            .any(|r| r.id == first.id && r.attendance == Attendance::Attending));
+    }
+
+    #[tokio::test]
+    async fn in_memory_answers_replace_earlier_answers_by_name() {
+        answers_replace_earlier_answers_by_name(&InMemoryRsvpRepository::default()).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_answers_replace_earlier_answers_by_name() {
+        let path = std::env::temp_dir().join(format!("bitebuds-{}.db", uuid::Uuid::new_v4()));
+        let events =
+            SqliteEventRepository::connect(&path, Arc::new(InMemoryEventRepository::default()))
+                .await
+                .unwrap();
+
+        answers_replace_earlier_answers_by_name(&SqliteRsvpRepository::new(events.pool().clone()))
+            .await;
+
+        events.pool().close().await;
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[test]
+    fn answers_are_validated() {
+        assert_eq!(
+            validate(" Mormor ", 2, Some("  ".into())).unwrap(),
+            ("Mormor".to_string(), 2, None)
+        );
+        assert!(validate("  ", 1, None).is_err());
+        assert!(validate("Mormor", 0, None).is_err());
+        assert!(validate("Mormor", 21, None).is_err());
+        assert!(validate("Mormor", 1, Some("x".repeat(501))).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate};
+use domain::units::{self, Dimension, Unit};
+use domain::{Recipe, ShoppingItem};
+use sqlx::sqlite::SqlitePool;
+use tokio::sync::RwLock;
+
+/// How many days a shopping list covers.
+pub const LIST_DAYS: i64 = 7;
+pub const MAX_KEY_LENGTH: usize = 200;
+
+/// The first day of the list shown when no day is picked. It is the Monday of
+/// the week, so the list, and what has been checked off on it, stays the same
+/// all week.
+pub fn default_from(today: NaiveDate) -> NaiveDate {
+    today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64)
+}
+
+#[derive(Default)]
+struct Entry {
+    name: String,
+    /// Quantities with the unit they were given in.
+    amounts: Vec<(f64, Option<String>)>,
+    recipes: Vec<String>,
+    notes: Vec<String>,
+}
+
+/// Sums the ingredients of the recipes, which are expected to be scaled to
+/// the meals they are planned for already, see [`Recipe::scaled_to`].
+pub fn build(recipes: &[Recipe]) -> Vec<ShoppingItem> {
+    let mut entries: BTreeMap<String, Entry> = BTreeMap::new();
+
+    for recipe in recipes {
+        for ingredient in &recipe.ingredients {
+            let name = ingredient.name.trim();
+            if name.is_empty() {
+                continue;
+            }
+
+            // Known units are written the same way, so `Spiseske` and `spsk`
+            // are added up.
+            let unit = ingredient
+                .unit
+                .as_deref()
+                .map(|unit| match Unit::parse(unit) {
+                    Some(unit) => unit.symbol().to_string(),
+                    None => unit.trim().to_lowercase(),
+                })
+                .filter(|unit| !unit.is_empty());
+            let unit_key = match unit.as_deref().and_then(Unit::parse) {
+                Some(unit) => match unit.dimension() {
+                    Some(Dimension::Weight) => "weight".to_string(),
+                    Some(Dimension::Volume) => "volume".to_string(),
+                    None => unit.symbol().to_string(),
+                },
+                None => unit.clone().unwrap_or_default(),
+            };
+
+            let entry = entries
+                .entry(format!("{}:{unit_key}", name.to_lowercase()))
+                .or_default();
+            if entry.name.is_empty() {
+                entry.name = name.to_string();
+            }
+            if let Some(quantity) = ingredient.quantity {
+                entry.amounts.push((quantity, unit.clone()));
+            }
+            if !entry.recipes.contains(&recipe.name) {
+                entry.recipes.push(recipe.name.clone());
+            }
+            if let Some(note) = ingredient.note.as_ref().map(|note| note.trim().to_string()) {
+                if !note.is_empty() && !entry.notes.contains(&note) {
+                    entry.notes.push(note);
+                }
+            }
+        }
+    }
+
+    entries
+        .into_iter()
+        .map(|(key, entry)| {
+            let (quantity, unit) = total(&entry.amounts);
+
+            ShoppingItem {
+                key,
+                name: entry.name,
+                quantity,
+                unit,
+                recipes: entry.recipes,
+                notes: entry.notes,
+                checked: false,
+            }
+        })
+        .collect()
+}
+
+/// Adds up quantities of the same kind, in the smallest of their units, and
+/// gives the sum in the unit which reads best, see [`units::normalize`].
+fn total(amounts: &[(f64, Option<String>)]) -> (Option<f64>, Option<String>) {
+    let Some((_, first_unit)) = amounts.first() else {
+        return (None, None);
+    };
+
+    if amounts.iter().all(|(_, unit)| unit == first_unit) {
+        let sum = amounts.iter().map(|(quantity, _)| quantity).sum();
+
+        return match first_unit.as_deref().and_then(Unit::parse) {
+            Some(unit) => {
+                let (sum, unit) = units::normalize(sum, unit);
+                (Some(round(sum)), Some(unit.symbol().to_string()))
+            }
+            None => (Some(round(sum)), first_unit.clone()),
+        };
+    }
+
+    // Units only differ when they are all known units of the same dimension.
+    let known = amounts
+        .iter()
+        .filter_map(|(quantity, unit)| Some((*quantity, Unit::parse(unit.as_deref()?)?)))
+        .collect::<Vec<_>>();
+    let Some(smallest) = known
+        .iter()
+        .map(|(_, unit)| *unit)
+        .min_by(|a, b| a.convert(1.0, *b).unwrap_or(1.0).total_cmp(&1.0))
+    else {
+        return (None, None);
+    };
+
+    let sum = known
+        .iter()
+        .filter_map(|(quantity, unit)| unit.convert(*quantity, smallest))
+        .sum();
+    let (sum, unit) = units::normalize(sum, smallest);
+
+    (Some(round(sum)), Some(unit.symbol().to_string()))
+}
+
+fn round(quantity: f64) -> f64 {
+    (quantity * 100.0).round() / 100.0
+}
+
+/// Remembers what has been bought. A list is identified by the first day it
+/// covers, so moving to the next week starts a fresh list.
+#[async_trait]
+pub trait ShoppingListRepository: Send + Sync {
+    /// Keys of the items checked off on the list starting on `from`.
+    async fn list_checked(&self, from: NaiveDate) -> eyre::Result<HashSet<String>>;
+
+    async fn set_checked(&self, from: NaiveDate, key: &str, checked: bool) -> eyre::Result<()>;
+}
+
+/// Checked items are lost on restart, used when no database is configured.
+#[derive(Clone, Default)]
+pub struct InMemoryShoppingListRepository {
+    checked: Arc<RwLock<HashSet<(NaiveDate, String)>>>,
+}
+
+#[async_trait]
+impl ShoppingListRepository for InMemoryShoppingListRepository {
+    async fn list_checked(&self, from: NaiveDate) -> eyre::Result<HashSet<String>> {
+        Ok(self
+            .checked
+            .read()
+            .await
+            .iter()
+            .filter(|(list_from, _)| *list_from == from)
+            .map(|(_, key)| key.clone())
+            .collect())
+    }
+
+    async fn set_checked(&self, from: NaiveDate, key: &str, checked: bool) -> eyre::Result<()> {
+        let mut items = self.checked.write().await;
+        if checked {
+            items.insert((from, key.to_string()));
+        } else {
+            items.remove(&(from, key.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteShoppingListRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteShoppingListRepository {
+    /// The schema is created by the migrations run in
+    /// [`crate::SqliteEventRepository::connect`].
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ShoppingListRepository for SqliteShoppingListRepository {
+    async fn list_checked(&self, from: NaiveDate) -> eyre::Result<HashSet<String>> {
+        let rows: Vec<(String,)> =
+            sqlx::query_as("SELECT item_key FROM shopping_checks WHERE list_from = ?")
+                .bind(from.format("%Y-%m-%d").to_string())
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows.into_iter().map(|(key,)| key).collect())
+    }
+
+    async fn set_checked(&self, from: NaiveDate, key: &str, checked: bool) -> eyre::Result<()> {
+        let from = from.format("%Y-%m-%d").to_string();
+
+        if checked {
+            sqlx::query(
+                "INSERT INTO shopping_checks (list_from, item_key, checked_at) VALUES (?, ?, ?)
+                ON CONFLICT (list_from, item_key) DO NOTHING",
+            )
+            .bind(from)
+            .bind(key)
+            .bind(chrono::Utc::now().timestamp())
+            .execute(&self.pool)
+            .await?;
+        } else {
+            sqlx::query("DELETE FROM shopping_checks WHERE list_from = ? AND item_key = ?")
+                .bind(from)
+                .bind(key)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use chrono::NaiveDate;
+    use domain::{Ingredient, Recipe};
+
+    use crate::{InMemoryEventRepository, SqliteEventRepository};
+
+    use super::{
+        build, default_from, InMemoryShoppingListRepository, ShoppingListRepository,
+        SqliteShoppingListRepository,
+    };
+
+    fn ingredient(name: &str, quantity: Option<f64>, unit: Option<&str>) -> Ingredient {
+        Ingredient {
+            name: name.into(),
+            quantity,
+            unit: unit.map(|unit| unit.into()),
+            note: None,
+        }
+    }
+
+    fn recipe(name: &str, ingredients: Vec<Ingredient>) -> Recipe {
+        Recipe {
+            id: uuid::Uuid::new_v4(),
+            slug: name.to_lowercase(),
+            name: name.into(),
+            description: None,
+            servings: None,
+            prep_time_minutes: None,
+            cook_time_minutes: None,
+            ingredients,
+            steps: vec![],
+            references: vec![],
+            images: vec![],
+            metadata: None,
+            body_html: None,
+        }
+    }
+
+    #[test]
+    fn ingredients_are_merged_across_compatible_units() {
+        let recipes = vec![
+            recipe(
+                "Oksesteg",
+                vec![
+                    ingredient("Fløde", Some(2.0), Some("dl")),
+                    ingredient("Smør", Some(2.0), Some("spsk")),
+                    ingredient("Løg", Some(1.0), None),
+                    ingredient("Sukker", Some(1.0), Some("spsk")),
+                ],
+            ),
+            recipe(
+                "Kartoffelsuppe",
+                vec![
+                    ingredient("fløde", Some(1.0), Some("l")),
+                    ingredient("Smør", Some(50.0), Some("g")),
+                    ingredient("Smør", Some(1.0), Some("Tbsp")),
+                    ingredient("Sukker", Some(1.0), Some("tsk")),
+                    ingredient("Løg", Some(2.0), None),
+                ],
+            ),
+        ];
+
+        let items = build(&recipes);
+        let item = |key: &str| items.iter().find(|i| i.key == key).unwrap();
+
+        assert_eq!(items.len(), 5);
+        assert_eq!(item("fløde:volume").quantity, Some(1.2));
+        assert_eq!(item("fløde:volume").unit.as_deref(), Some("l"));
+        assert_eq!(
+            item("fløde:volume").recipes,
+            vec!["Oksesteg".to_string(), "Kartoffelsuppe".to_string()]
+        );
+        assert_eq!(item("løg:").quantity, Some(3.0));
+        // Butter by the spoon and by weight can't be added up.
+        assert_eq!(item("smør:volume").quantity, Some(3.0));
+        assert_eq!(item("smør:volume").unit.as_deref(), Some("spsk"));
+        assert_eq!(item("smør:weight").quantity, Some(50.0));
+        assert_eq!(item("sukker:volume").quantity, Some(1.33));
+        assert_eq!(item("sukker:volume").unit.as_deref(), Some("spsk"));
+    }
+
+    #[tokio::test]
+    async fn checks_stay_on_the_default_list_for_the_whole_week() {
+        let repository = InMemoryShoppingListRepository::default();
+        let tuesday = NaiveDate::from_ymd_opt(2023, 3, 7).unwrap();
+
+        repository
+            .set_checked(default_from(tuesday), "løg:", true)
+            .await
+            .unwrap();
+
+        for day in 8..=12 {
+            let from = default_from(NaiveDate::from_ymd_opt(2023, 3, day).unwrap());
+            assert_eq!(from, NaiveDate::from_ymd_opt(2023, 3, 6).unwrap());
+            assert!(repository
+                .list_checked(from)
+                .await
+                .unwrap()
+                .contains("løg:"));
+        }
+
+        let next_monday = NaiveDate::from_ymd_opt(2023, 3, 13).unwrap();
+        assert_eq!(default_from(next_monday), next_monday);
+        assert!(repository
+            .list_checked(default_from(next_monday))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    async fn checks_belong_to_their_list(repository: &dyn ShoppingListRepository) {
+        let this_week = NaiveDate::from_ymd_opt(2023, 3, 6).unwrap();
+        let next_week = NaiveDate::from_ymd_opt(2023, 3, 13).unwrap();
+
+        repository
+            .set_checked(this_week, "løg:", true)
+            .await
+            .unwrap();
+        repository
+            .set_checked(this_week, "løg:", true)
+            .await
+            .unwrap();
+        repository
+            .set_checked(this_week, "salt:", true)
+            .await
+            .unwrap();
+        repository
+            .set_checked(this_week, "salt:", false)
+            .await
+            .unwrap();
+
+        let checked = repository.list_checked(this_week).await.unwrap();
+        assert_eq!(checked.len(), 1);
+        assert!(checked.contains("løg:"));
+        assert!(repository.list_checked(next_week).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn in_memory_checks_belong_to_their_list() {
+        checks_belong_to_their_list(&InMemoryShoppingListRepository::default()).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_checks_belong_to_their_list() {
+        let path = std::env::temp_dir().join(format!("bitebuds-{}.db", uuid::Uuid::new_v4()));
+        let events =
+            SqliteEventRepository::connect(&path, Arc::new(InMemoryEventRepository::default()))
+                .await
+                .unwrap();
+
+        checks_belong_to_their_list(&SqliteShoppingListRepository::new(events.pool().clone()))
+            .await;
+
+        events.pool().close().await;
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+use cfg_if::cfg_if;
+use leptos::*;
+
+use domain::AuthorSession;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use axum::http::{header, HeaderValue};
+
+        use crate::auth::{
+            auth, current_author, expired_session_cookie, request_headers, require_author,
+            same_origin, session_cookie, session_token,
+        };
+
+        fn set_cookie(cx: Scope, cookie: HeaderValue) {
+            if let Some(response) = use_context::<leptos_axum::ResponseOptions>(cx) {
+                response.insert_header(header::SET_COOKIE, cookie);
+            }
+        }
+
+        async fn get_session_fn(cx: Scope) -> Result<Option<AuthorSession>, ServerFnError> {
+            current_author(cx).await
+        }
+        async fn login_fn(cx: Scope, name: String, password: String) -> Result<AuthorSession, ServerFnError> {
+            if !same_origin(&request_headers(cx)?) {
+                return Err(ServerFnError::ServerError("requests from other sites are not allowed".into()));
+            }
+
+            let (token, session) = auth(cx)?
+                .login(&name, &password)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))?
+                .ok_or_else(|| ServerFnError::ServerError("wrong name or password".into()))?;
+
+            set_cookie(cx, session_cookie(&token));
+
+            Ok(session)
+        }
+        async fn logout_fn(cx: Scope, csrf_token: String) -> Result<(), ServerFnError> {
+            require_author(cx, &csrf_token).await?;
+
+            if let Some(token) = session_token(&request_headers(cx)?) {
+                auth(cx)?
+                    .logout(&token)
+                    .await
+                    .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+            }
+            set_cookie(cx, expired_session_cookie());
+
+            Ok(())
+        }
+    }
+}
+
+/// The logged in author, including the csrf token privileged actions need.
+#[server(GetSession, "/api")]
+pub async fn get_session(cx: Scope) -> Result<Option<AuthorSession>, ServerFnError> {
+    get_session_fn(cx).await
+}
+
+#[server(Login, "/api")]
+pub async fn login(cx: Scope, name: String, password: String) -> Result<AuthorSession, ServerFnError> {
+    login_fn(cx, name, password).await
+}
+
+#[server(Logout, "/api")]
+pub async fn logout(cx: Scope, csrf_token: String) -> Result<(), ServerFnError> {
+    logout_fn(cx, csrf_token).await
+}
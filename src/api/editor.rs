@@ -0,0 +1,160 @@
+use cfg_if::cfg_if;
+use leptos::*;
+
+use domain::EditableEvent;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use domain::{EventDraft, MealSlot};
+
+        use super::event_store;
+        use crate::auth::{current_author, require_author};
+
+        fn non_empty(value: String) -> Option<String> {
+            Some(value.trim().to_string()).filter(|value| !value.is_empty())
+        }
+
+        fn bad_request(message: impl Into<String>) -> ServerFnError {
+            ServerFnError::ServerError(message.into())
+        }
+
+        async fn get_editable_event_fn(cx: Scope, slug: String) -> Result<Option<EditableEvent>, ServerFnError> {
+            if current_author(cx).await?.is_none() {
+                return Err(ServerFnError::ServerError("you need to log in".into()));
+            }
+
+            event_store(cx)?
+                .get_editable_event(&slug)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+        #[allow(clippy::too_many_arguments)]
+        async fn save_event_fn(
+            cx: Scope,
+            csrf_token: String,
+            slug: String,
+            base_commit: String,
+            name: String,
+            description: String,
+            date: String,
+            start_time: String,
+            meal: String,
+            servings: String,
+            tags: String,
+            recipe: String,
+            cover_image_url: String,
+            cover_image_alt: String,
+            body: String,
+        ) -> Result<String, ServerFnError> {
+            let author = require_author(cx, &csrf_token).await?;
+
+            let date = chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|_| bad_request("the date should be like 2023-03-06"))?;
+            let start_time = non_empty(start_time)
+                .map(|time| chrono::NaiveTime::parse_from_str(&time, "%H:%M"))
+                .transpose()
+                .map_err(|_| bad_request("the time should be like 18:30"))?;
+            let meal = match meal.as_str() {
+                "" => None,
+                "breakfast" => Some(MealSlot::Breakfast),
+                "lunch" => Some(MealSlot::Lunch),
+                "dinner" => Some(MealSlot::Dinner),
+                "snack" => Some(MealSlot::Snack),
+                _ => return Err(bad_request(format!("unknown meal {meal}"))),
+            };
+            let servings = non_empty(servings)
+                .map(|servings| servings.parse::<u32>())
+                .transpose()
+                .map_err(|_| bad_request("servings should be a number of people"))?;
+
+            let draft = EventDraft {
+                name,
+                description: non_empty(description),
+                date,
+                start_time,
+                meal,
+                servings,
+                tags: tags.split(',').map(|tag| tag.to_string()).collect(),
+                recipe: non_empty(recipe),
+                cover_image_url: non_empty(cover_image_url),
+                cover_image_alt: non_empty(cover_image_alt),
+                body,
+            };
+
+            event_store(cx)?
+                .save_event(&author.name, non_empty(slug).as_deref(), draft, non_empty(base_commit))
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+        async fn delete_event_fn(
+            cx: Scope,
+            csrf_token: String,
+            slug: String,
+            base_commit: String,
+        ) -> Result<(), ServerFnError> {
+            let author = require_author(cx, &csrf_token).await?;
+
+            event_store(cx)?
+                .delete_event(&author.name, &slug, non_empty(base_commit))
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+    }
+}
+
+/// The markdown behind an event, for authors only.
+#[server(GetEditableEvent, "/api")]
+pub async fn get_editable_event(cx: Scope, slug: String) -> Result<Option<EditableEvent>, ServerFnError> {
+    get_editable_event_fn(cx, slug).await
+}
+
+/// Writes the event to the content repository, as a new event when `slug` is
+/// empty, and returns its slug. Fields are passed as they come from the form.
+#[allow(clippy::too_many_arguments)]
+#[server(SaveEvent, "/api")]
+pub async fn save_event(
+    cx: Scope,
+    csrf_token: String,
+    slug: String,
+    base_commit: String,
+    name: String,
+    description: String,
+    date: String,
+    start_time: String,
+    meal: String,
+    servings: String,
+    tags: String,
+    recipe: String,
+    cover_image_url: String,
+    cover_image_alt: String,
+    body: String,
+) -> Result<String, ServerFnError> {
+    save_event_fn(
+        cx,
+        csrf_token,
+        slug,
+        base_commit,
+        name,
+        description,
+        date,
+        start_time,
+        meal,
+        servings,
+        tags,
+        recipe,
+        cover_image_url,
+        cover_image_alt,
+        body,
+    )
+    .await
+}
+
+#[server(DeleteEvent, "/api")]
+pub async fn delete_event(
+    cx: Scope,
+    csrf_token: String,
+    slug: String,
+    base_commit: String,
+) -> Result<(), ServerFnError> {
+    delete_event_fn(cx, csrf_token, slug, base_commit).await
+}
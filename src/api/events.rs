@@ -1,43 +1,163 @@
-use std::path::PathBuf;
-
 use cfg_if::cfg_if;
 use leptos::*;
 use serde::{Deserialize, Serialize};
 
-use domain::{Event, EventOverview};
+use chrono::NaiveDate;
+use domain::{Event, EventOverview, EventPage, ParseDiagnostic, SortOrder, SyncStatus};
 
 cfg_if! {
     if #[cfg(feature = "ssr")] {
-        use services::EventStore;
-        use lazy_static::lazy_static;
+        use super::event_store;
+        use crate::auth::current_author;
+        use crate::calendar::{grid, last_day_of_month, parse_month, parse_week, start_of_week};
+        use chrono::Datelike;
 
-        lazy_static! {
-            static ref EVENTSTORE: EventStore = EventStore::new(PathBuf::from("articles"));
-        }
-        async fn get_upcoming_events_fn() -> Result<UpcomingEventsOverview, ServerFnError> {
-            let mut events: Vec<EventOverview> = EVENTSTORE
+        async fn get_upcoming_events_fn(cx: Scope) -> Result<UpcomingEventsOverview, ServerFnError> {
+            let mut events: Vec<EventOverview> = event_store(cx)?
                 .get_upcoming_events()
                 .await
                 .map_err(|e| ServerFnError::ServerError(e.to_string()))?
-                .iter()
-                .filter(|d| d.time.ge(&chrono::Utc::now().date_naive()))
-                .map(|data| data.clone().into())
+                .into_iter()
+                .map(|data| data.into())
                 .collect();
 
-            events.sort_by(|a, b| a.time.cmp(&b.time))          ;
+            events.sort_by_key(|e| e.sort_key());
 
             Ok(UpcomingEventsOverview { events })
         }
-        async fn get_full_event_fn(event_id: uuid::Uuid) -> Result<Option<Event>, ServerFnError> {
-            let event = EVENTSTORE
+        async fn get_full_event_fn(cx: Scope, event_id: uuid::Uuid) -> Result<Option<Event>, ServerFnError> {
+            let event = event_store(cx)?
                 .get_event(event_id)
                 .await
                 .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
             Ok(event)
         }
+        async fn get_event_by_slug_fn(cx: Scope, slug: String) -> Result<Option<Event>, ServerFnError> {
+            event_store(cx)?
+                .get_event_by_slug(&slug)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+        async fn get_events_fn(
+            cx: Scope,
+            from: NaiveDate,
+            until: NaiveDate,
+            order: SortOrder,
+            cursor: Option<String>,
+            limit: Option<usize>,
+        ) -> Result<EventPage, ServerFnError> {
+            event_store(cx)?
+                .query_events(from, until, order, cursor.as_deref(), limit)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+        async fn get_archive_fn(
+            cx: Scope,
+            month: Option<String>,
+            cursor: Option<String>,
+        ) -> Result<ArchiveMonth, ServerFnError> {
+            let store = event_store(cx)?;
+            let today = store.today();
+            let this_month = today.with_day(1).expect("first day of month");
+
+            let month = match month {
+                Some(month) => parse_month(&month)
+                    .ok_or_else(|| ServerFnError::Args(format!("invalid month {month}")))?,
+                None => this_month,
+            };
+            let until = last_day_of_month(month)
+                .ok_or_else(|| ServerFnError::Args(format!("invalid month {}", month.format("%Y-%m"))))?
+                .min(today.pred_opt().expect("day within range"));
+            if until < month {
+                // The month hasn't started yet, so nothing is archived.
+                return Ok(ArchiveMonth {
+                    month,
+                    page: EventPage { events: vec![], next_cursor: None },
+                    has_next_month: false,
+                });
+            }
+
+            let page = store
+                .query_events(month, until, SortOrder::Descending, cursor.as_deref(), None)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+
+            Ok(ArchiveMonth {
+                month,
+                page,
+                has_next_month: month < this_month,
+            })
+        }
+        async fn get_calendar_fn(
+            cx: Scope,
+            first: NaiveDate,
+            last: NaiveDate,
+        ) -> Result<CalendarOverview, ServerFnError> {
+            let store = event_store(cx)?;
+            let days = grid(first, last)
+                .ok_or_else(|| ServerFnError::Args(format!("no calendar for {first} to {last}")))?;
+            let events = store
+                .get_events_between(days[0], days[days.len() - 1])
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))?
+                .into_iter()
+                .map(|event| event.into())
+                .collect();
+
+            Ok(CalendarOverview {
+                first,
+                last,
+                today: store.today(),
+                events,
+            })
+        }
+        async fn get_week_fn(cx: Scope, week: Option<String>) -> Result<CalendarOverview, ServerFnError> {
+            let monday = match week {
+                Some(week) => parse_week(&week)
+                    .ok_or_else(|| ServerFnError::Args(format!("invalid week {week}")))?,
+                None => start_of_week(event_store(cx)?.today()),
+            };
+
+            let sunday = monday
+                .checked_add_signed(chrono::Duration::days(6))
+                .ok_or_else(|| ServerFnError::Args(format!("invalid week {}", monday.format("%Y-%m-%d"))))?;
+
+            get_calendar_fn(cx, monday, sunday).await
+        }
+        async fn get_month_fn(cx: Scope, month: Option<String>) -> Result<CalendarOverview, ServerFnError> {
+            let first = match month {
+                Some(month) => parse_month(&month)
+                    .ok_or_else(|| ServerFnError::Args(format!("invalid month {month}")))?,
+                None => event_store(cx)?.today().with_day(1).expect("first day of month"),
+            };
+
+            let last = last_day_of_month(first)
+                .ok_or_else(|| ServerFnError::Args(format!("invalid month {}", first.format("%Y-%m"))))?;
+
+            get_calendar_fn(cx, first, last).await
+        }
+        async fn get_diagnostics_fn(cx: Scope) -> Result<Vec<ParseDiagnostic>, ServerFnError> {
+            // Diagnostics name files in the content repository.
+            if current_author(cx).await?.is_none() {
+                return Err(ServerFnError::ServerError("you need to log in".into()));
+            }
+
+            event_store(cx)?
+                .get_diagnostics()
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+        async fn get_sync_status_fn(cx: Scope) -> Result<SyncStatus, ServerFnError> {
+            // The last error is output from git, which may include the
+            // repository url.
+            if current_author(cx).await?.is_none() {
+                return Err(ServerFnError::ServerError("you need to log in".into()));
+            }
 
-        pub async fn boostrap() -> Result<(), ServerFnError> {
-            EVENTSTORE.bootstrap().await.map_err(|e| ServerFnError::ServerError(e.to_string()))
+            event_store(cx)?
+                .get_sync_status()
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
         }
     }
 }
@@ -47,12 +167,83 @@ pub struct UpcomingEventsOverview {
     pub events: Vec<EventOverview>,
 }
 
+/// Past events of a single month, newest first.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveMonth {
+    /// First day of the month.
+    pub month: NaiveDate,
+    pub page: EventPage,
+    /// Whether the following month has started, and so has an archive too.
+    pub has_next_month: bool,
+}
+
+/// Events of a week or month, covering the whole weeks the period is shown in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarOverview {
+    /// First day of the period.
+    pub first: NaiveDate,
+    /// Last day of the period.
+    pub last: NaiveDate,
+    pub today: NaiveDate,
+    pub events: Vec<EventOverview>,
+}
+
 #[server(GetUpcomingEvents, "/api")]
-pub async fn get_upcoming_events() -> Result<UpcomingEventsOverview, ServerFnError> {
-    get_upcoming_events_fn().await
+pub async fn get_upcoming_events(cx: Scope) -> Result<UpcomingEventsOverview, ServerFnError> {
+    get_upcoming_events_fn(cx).await
 }
 
 #[server(GetFullEvent, "/api")]
-pub async fn get_full_event(event_id: uuid::Uuid) -> Result<Option<Event>, ServerFnError> {
-    get_full_event_fn(event_id).await
+pub async fn get_full_event(cx: Scope, event_id: uuid::Uuid) -> Result<Option<Event>, ServerFnError> {
+    get_full_event_fn(cx, event_id).await
+}
+
+#[server(GetEventBySlug, "/api")]
+pub async fn get_event_by_slug(cx: Scope, slug: String) -> Result<Option<Event>, ServerFnError> {
+    get_event_by_slug_fn(cx, slug).await
+}
+
+#[server(GetDiagnostics, "/api")]
+pub async fn get_diagnostics(cx: Scope) -> Result<Vec<ParseDiagnostic>, ServerFnError> {
+    get_diagnostics_fn(cx).await
+}
+
+#[server(GetSyncStatus, "/api")]
+pub async fn get_sync_status(cx: Scope) -> Result<SyncStatus, ServerFnError> {
+    get_sync_status_fn(cx).await
+}
+
+#[server(GetEvents, "/api")]
+pub async fn get_events(
+    cx: Scope,
+    from: NaiveDate,
+    until: NaiveDate,
+    order: SortOrder,
+    cursor: Option<String>,
+    limit: Option<usize>,
+) -> Result<EventPage, ServerFnError> {
+    get_events_fn(cx, from, until, order, cursor, limit).await
+}
+
+/// Past events of `month`, such as `2023-03`, or of the current month when
+/// left out.
+#[server(GetArchive, "/api")]
+pub async fn get_archive(
+    cx: Scope,
+    month: Option<String>,
+    cursor: Option<String>,
+) -> Result<ArchiveMonth, ServerFnError> {
+    get_archive_fn(cx, month, cursor).await
+}
+
+/// The week, such as `2023-W10`, or the current week when left out.
+#[server(GetWeek, "/api")]
+pub async fn get_week(cx: Scope, week: Option<String>) -> Result<CalendarOverview, ServerFnError> {
+    get_week_fn(cx, week).await
+}
+
+/// The month, such as `2023-03`, or the current month when left out.
+#[server(GetMonth, "/api")]
+pub async fn get_month(cx: Scope, month: Option<String>) -> Result<CalendarOverview, ServerFnError> {
+    get_month_fn(cx, month).await
 }
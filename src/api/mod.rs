@@ -1,9 +1,45 @@
+pub mod auth;
+pub mod editor;
 pub mod events;
+pub mod recipes;
+pub mod rsvps;
+pub mod shopping;
+pub mod site;
 
 use leptos::*;
 
+/// The event store is provided to every server function by the server, see
+/// `crate::state::AppState`.
+#[cfg(feature = "ssr")]
+pub(crate) fn event_store(cx: Scope) -> Result<services::EventStore, ServerFnError> {
+    use_context::<services::EventStore>(cx)
+        .ok_or_else(|| ServerFnError::ServerError("event store is not provided".into()))
+}
+
 #[cfg(feature = "ssr")]
 pub fn register() {
+    auth::GetSession::register();
+    auth::Login::register();
+    auth::Logout::register();
+    editor::GetEditableEvent::register();
+    editor::SaveEvent::register();
+    editor::DeleteEvent::register();
     events::GetUpcomingEvents::register();
     events::GetFullEvent::register();
+    events::GetEventBySlug::register();
+    events::GetEvents::register();
+    events::GetArchive::register();
+    events::GetWeek::register();
+    events::GetMonth::register();
+    events::GetDiagnostics::register();
+    events::GetSyncStatus::register();
+    recipes::GetRecipe::register();
+    recipes::GetRecipeBySlug::register();
+    recipes::GetPlannedServings::register();
+    rsvps::GetAttendees::register();
+    rsvps::RespondToEvent::register();
+    shopping::GetShoppingList::register();
+    shopping::CheckShoppingItem::register();
+    site::GetAnalytics::register();
+    site::GetSiteUrl::register();
 }
@@ -0,0 +1,48 @@
+use cfg_if::cfg_if;
+use leptos::*;
+
+use domain::Recipe;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use super::event_store;
+
+        async fn get_recipe_fn(cx: Scope, recipe_id: uuid::Uuid) -> Result<Option<Recipe>, ServerFnError> {
+            let recipe = event_store(cx)?
+                .get_recipe(recipe_id)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+            Ok(recipe)
+        }
+        async fn get_recipe_by_slug_fn(cx: Scope, slug: String) -> Result<Option<Recipe>, ServerFnError> {
+            let recipe = event_store(cx)?
+                .get_recipe_by_slug(&slug)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+            Ok(recipe)
+        }
+        async fn get_planned_servings_fn(cx: Scope, event_id: uuid::Uuid) -> Result<Option<u32>, ServerFnError> {
+            event_store(cx)?
+                .get_planned_servings(event_id)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+    }
+}
+
+#[server(GetRecipe, "/api")]
+pub async fn get_recipe(cx: Scope, recipe_id: uuid::Uuid) -> Result<Option<Recipe>, ServerFnError> {
+    get_recipe_fn(cx, recipe_id).await
+}
+
+#[server(GetRecipeBySlug, "/api")]
+pub async fn get_recipe_by_slug(cx: Scope, slug: String) -> Result<Option<Recipe>, ServerFnError> {
+    get_recipe_by_slug_fn(cx, slug).await
+}
+
+/// How many people the recipe of the event should be scaled to, from the
+/// servings the meal is planned for or the guests who are coming.
+#[server(GetPlannedServings, "/api")]
+pub async fn get_planned_servings(cx: Scope, event_id: uuid::Uuid) -> Result<Option<u32>, ServerFnError> {
+    get_planned_servings_fn(cx, event_id).await
+}
@@ -0,0 +1,49 @@
+use cfg_if::cfg_if;
+use leptos::*;
+
+use domain::{Attendance, Attendees, Rsvp};
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use super::event_store;
+
+        async fn get_attendees_fn(cx: Scope, event_id: uuid::Uuid) -> Result<Attendees, ServerFnError> {
+            event_store(cx)?
+                .get_attendees(event_id)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+        async fn respond_to_event_fn(
+            cx: Scope,
+            event_id: uuid::Uuid,
+            name: String,
+            attendance: Attendance,
+            headcount: u32,
+            note: Option<String>,
+        ) -> Result<Rsvp, ServerFnError> {
+            event_store(cx)?
+                .respond(event_id, &name, attendance, headcount, note)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+    }
+}
+
+#[server(GetAttendees, "/api")]
+pub async fn get_attendees(cx: Scope, event_id: uuid::Uuid) -> Result<Attendees, ServerFnError> {
+    get_attendees_fn(cx, event_id).await
+}
+
+/// Records whether a guest joins the meal. Answering again with the same name
+/// changes the earlier answer.
+#[server(RespondToEvent, "/api")]
+pub async fn respond_to_event(
+    cx: Scope,
+    event_id: uuid::Uuid,
+    name: String,
+    attendance: Attendance,
+    headcount: u32,
+    note: Option<String>,
+) -> Result<Rsvp, ServerFnError> {
+    respond_to_event_fn(cx, event_id, name, attendance, headcount, note).await
+}
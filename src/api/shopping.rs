@@ -0,0 +1,68 @@
+use cfg_if::cfg_if;
+use leptos::*;
+
+use domain::ShoppingList;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use services::shopping::{default_from, LIST_DAYS};
+
+        use super::event_store;
+        use crate::auth::require_author;
+
+        fn parse_day(day: &str) -> Result<chrono::NaiveDate, ServerFnError> {
+            chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d")
+                .map_err(|_| ServerFnError::Args(format!("invalid date {day}")))
+        }
+
+        async fn get_shopping_list_fn(cx: Scope, from: Option<String>) -> Result<ShoppingList, ServerFnError> {
+            let store = event_store(cx)?;
+            let from = match from {
+                Some(from) => parse_day(&from)?,
+                None => default_from(store.today()),
+            };
+            let until = from
+                .checked_add_signed(chrono::Duration::days(LIST_DAYS - 1))
+                .ok_or_else(|| ServerFnError::Args(format!("invalid date {}", from.format("%Y-%m-%d"))))?;
+
+            store
+                .get_shopping_list(from, until)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+        async fn check_shopping_item_fn(
+            cx: Scope,
+            csrf_token: String,
+            from: String,
+            key: String,
+            checked: bool,
+        ) -> Result<(), ServerFnError> {
+            require_author(cx, &csrf_token).await?;
+
+            event_store(cx)?
+                .check_shopping_item(parse_day(&from)?, &key, checked)
+                .await
+                .map_err(|e| ServerFnError::ServerError(e.to_string()))
+        }
+    }
+}
+
+/// What to buy for the week starting on `from`, such as `2023-03-06`, or on
+/// the Monday of this week when left out.
+#[server(GetShoppingList, "/api")]
+pub async fn get_shopping_list(cx: Scope, from: Option<String>) -> Result<ShoppingList, ServerFnError> {
+    get_shopping_list_fn(cx, from).await
+}
+
+/// Marks an item on the list starting on `from` as bought, or not. Only
+/// authors can change the list.
+#[server(CheckShoppingItem, "/api")]
+pub async fn check_shopping_item(
+    cx: Scope,
+    csrf_token: String,
+    from: String,
+    key: String,
+    checked: bool,
+) -> Result<(), ServerFnError> {
+    check_shopping_item_fn(cx, csrf_token, from, key, checked).await
+}
@@ -0,0 +1,34 @@
+use cfg_if::cfg_if;
+use leptos::*;
+
+use crate::app::Analytics;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use crate::auth::request_headers;
+        use crate::state::{site_url, SiteUrl};
+
+        async fn get_analytics_fn(cx: Scope) -> Result<Option<Analytics>, ServerFnError> {
+            Ok(use_context::<Analytics>(cx))
+        }
+        async fn get_site_url_fn(cx: Scope) -> Result<String, ServerFnError> {
+            let configured = use_context::<SiteUrl>(cx).and_then(|site_url| site_url.0);
+
+            Ok(site_url(configured.as_deref(), &request_headers(cx)?))
+        }
+    }
+}
+
+/// The analytics script, if the server is configured with one. The app
+/// renders it on both the server and the client, so it has to ask for it.
+#[server(GetAnalytics, "/api")]
+pub async fn get_analytics(cx: Scope) -> Result<Option<Analytics>, ServerFnError> {
+    get_analytics_fn(cx).await
+}
+
+/// The public url of the site, such as `https://bitebuds.example.com`, for
+/// links which have to be absolute.
+#[server(GetSiteUrl, "/api")]
+pub async fn get_site_url(cx: Scope) -> Result<String, ServerFnError> {
+    get_site_url_fn(cx).await
+}
@@ -1,27 +1,83 @@
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
+use serde::{Deserialize, Serialize};
 
+use crate::api::site::get_analytics;
+
+use crate::pages::archive::*;
+use crate::pages::calendar::*;
+use crate::pages::editor::*;
+use crate::pages::event::*;
 use crate::pages::home::*;
+use crate::pages::login::*;
+use crate::pages::shopping::*;
+
+/// Plausible analytics, provided as context by the server when configured.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Analytics {
+    pub domain: String,
+    pub script_src: String,
+}
 
 #[component]
 pub fn App(cx: Scope) -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context(cx);
 
+    // Only the server knows the configuration, so the client gets it through
+    // a resource to render the same script when hydrating.
+    let analytics = create_resource(cx, || (), move |_| get_analytics(cx));
+    let analytics_view = move || {
+        analytics
+            .with(cx, |analytics| analytics.clone().ok().flatten())
+            .flatten()
+            .map(|analytics| view! { cx,
+                <script defer="true" data-domain=analytics.domain src=analytics.script_src/>
+            })
+    };
+
     view! { cx,
         <Stylesheet id="leptos" href="/pkg/ssr_modes.css" />
         <Title text="Bitebuds" />
 
-        <script defer="true" data-domain="bitebuds.front.kjuulh.io" src="https://plausible.front.kjuulh.io/js/script.js"/>
+        <Suspense fallback=|| ()>
+            {analytics_view}
+        </Suspense>
 
         <Router>
             <div class="app grid lg:grid-cols-[25%,50%,25%] sm:grid-cols-[10%,80%,10%] grid-cols-[5%,90%,5%]">
                 <main class="main col-start-2">
                     <div class="pt-4">
-                        <h1 class="font-semibold text-xl tracking-wide">"Bitebuds"</h1>
+                        <div class="flex items-baseline justify-between">
+                            <h1 class="font-semibold text-xl tracking-wide">
+                                <a href="/">"Bitebuds"</a>
+                            </h1>
+                            <nav class="space-x-4 text-sm text-gray-500">
+                                <A href="/">"Upcoming"</A>
+                                <A href="/week">"Week"</A>
+                                <A href="/month">"Month"</A>
+                                <A href="/archive">"Archive"</A>
+                                <A href="/shopping-list">"Shopping"</A>
+                            </nav>
+                        </div>
                         <Routes>
                             <Route path="" view=|cx| view! { cx, <HomePage /> }/>
+                            <Route path="archive" view=|cx| view! { cx, <ArchivePage /> }/>
+                            <Route path="archive/:month" view=|cx| view! { cx, <ArchivePage /> }/>
+                            <Route
+                                path="events/:slug"
+                                view=|cx| view! { cx, <EventDetailPage /> }
+                                ssr=SsrMode::Async
+                            />
+                            <Route path="events/:slug/edit" view=|cx| view! { cx, <EditEventPage /> }/>
+                            <Route path="new" view=|cx| view! { cx, <NewEventPage /> }/>
+                            <Route path="week" view=|cx| view! { cx, <WeekPage /> }/>
+                            <Route path="week/:week" view=|cx| view! { cx, <WeekPage /> }/>
+                            <Route path="month" view=|cx| view! { cx, <MonthPage /> }/>
+                            <Route path="month/:month" view=|cx| view! { cx, <MonthPage /> }/>
+                            <Route path="login" view=|cx| view! { cx, <LoginPage /> }/>
+                            <Route path="shopping-list" view=|cx| view! { cx, <ShoppingListPage /> }/>
                         </Routes>
                     </div>
                 </main>
@@ -0,0 +1,107 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use domain::AuthorSession;
+use leptos::{use_context, Scope, ServerFnError};
+use services::auth::{tokens_match, SESSION_DAYS};
+use services::Auth;
+
+pub const SESSION_COOKIE: &str = "bitebuds_session";
+
+/// The cookie is out of reach of scripts, only sent over https, and not sent
+/// along with requests started by other sites.
+pub fn session_cookie(token: &str) -> HeaderValue {
+    let max_age = SESSION_DAYS * 24 * 60 * 60;
+
+    HeaderValue::from_str(&format!(
+        "{SESSION_COOKIE}={token}; Path=/; Max-Age={max_age}; HttpOnly; Secure; SameSite=Lax"
+    ))
+    .expect("session tokens are url safe")
+}
+
+pub fn expired_session_cookie() -> HeaderValue {
+    HeaderValue::from_str(&format!(
+        "{SESSION_COOKIE}=; Path=/; Max-Age=0; HttpOnly; Secure; SameSite=Lax"
+    ))
+    .expect("cookie is valid")
+}
+
+pub fn session_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, token)| token.to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Requests from browsers carry the origin of the page they were sent from,
+/// which has to be this site. Together with `SameSite=Lax` this keeps other
+/// sites from acting on behalf of an author.
+pub fn same_origin(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let Some(host) = headers.get(header::HOST).and_then(|h| h.to_str().ok()) else {
+        return false;
+    };
+
+    origin
+        .to_str()
+        .ok()
+        .and_then(|origin| origin.split_once("://"))
+        .is_some_and(|(_, origin_host)| origin_host == host)
+}
+
+pub async fn session_from_headers(
+    auth: &Auth,
+    headers: &HeaderMap,
+) -> eyre::Result<Option<AuthorSession>> {
+    match session_token(headers) {
+        Some(token) => auth.session(&token).await,
+        None => Ok(None),
+    }
+}
+
+/// Auth is provided to every server function by the server, see
+/// [`crate::state::AppState`].
+pub(crate) fn auth(cx: Scope) -> Result<Auth, ServerFnError> {
+    use_context::<Auth>(cx).ok_or_else(|| ServerFnError::ServerError("auth is not provided".into()))
+}
+
+pub(crate) fn request_headers(cx: Scope) -> Result<HeaderMap, ServerFnError> {
+    use_context::<leptos_axum::RequestParts>(cx)
+        .map(|parts| parts.headers)
+        .ok_or_else(|| ServerFnError::ServerError("request is not provided".into()))
+}
+
+/// The logged in author, if any.
+pub async fn current_author(cx: Scope) -> Result<Option<AuthorSession>, ServerFnError> {
+    session_from_headers(&auth(cx)?, &request_headers(cx)?)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+/// Guards server functions which only authors may call. They take the csrf
+/// token of the session as an argument, usually from a hidden form field, and
+/// pass it on here.
+pub async fn require_author(cx: Scope, csrf_token: &str) -> Result<AuthorSession, ServerFnError> {
+    if !same_origin(&request_headers(cx)?) {
+        return Err(ServerFnError::ServerError(
+            "requests from other sites are not allowed".into(),
+        ));
+    }
+
+    let session = current_author(cx)
+        .await?
+        .ok_or_else(|| ServerFnError::ServerError("you need to log in".into()))?;
+
+    if !tokens_match(csrf_token, &session.csrf_token) {
+        return Err(ServerFnError::ServerError(
+            "your session has changed, please reload the page".into(),
+        ));
+    }
+
+    Ok(session)
+}
@@ -0,0 +1,45 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Parses a month such as `2023-03` into its first day.
+pub fn parse_month(month: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d").ok()
+}
+
+pub fn format_month(first_day: NaiveDate) -> String {
+    first_day.format("%Y-%m").to_string()
+}
+
+/// The last day of the month, or `None` for the last month there is.
+pub fn last_day_of_month(first_day: NaiveDate) -> Option<NaiveDate> {
+    first_day
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next_month| next_month.pred_opt())
+}
+
+/// Parses an ISO week such as `2023-W10` into its Monday.
+pub fn parse_week(week: &str) -> Option<NaiveDate> {
+    let (year, week) = week.split_once("-W").or_else(|| week.split_once("-w"))?;
+
+    NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, Weekday::Mon)
+}
+
+pub fn format_week(date: NaiveDate) -> String {
+    let week = date.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+pub fn start_of_week(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// The days shown in a calendar covering `first..=last`, padded out to whole
+/// weeks from Monday to Sunday, or `None` if those weeks run past the first
+/// or last day there is.
+pub fn grid(first: NaiveDate, last: NaiveDate) -> Option<Vec<NaiveDate>> {
+    let start =
+        first.checked_sub_signed(Duration::days(first.weekday().num_days_from_monday() as i64))?;
+    let end = last
+        .checked_add_signed(Duration::days(6 - last.weekday().num_days_from_monday() as i64))?;
+
+    Some(start.iter_days().take_while(|d| *d <= end).collect())
+}
@@ -0,0 +1,110 @@
+use leptos::*;
+use leptos_router::*;
+
+use crate::api::rsvps::*;
+use domain::{Attendance, Attendees, Rsvp};
+
+/// Who is joining the meal, and a form for guests to give their answer.
+#[component]
+pub fn AttendeeSection(cx: Scope, event_id: uuid::Uuid) -> impl IntoView {
+    let respond = create_server_action::<RespondToEvent>(cx);
+    let attendees = create_resource(
+        cx,
+        move || respond.version().get(),
+        move |_| get_attendees(cx, event_id),
+    );
+
+    let attendees_view = move || {
+        attendees.with(cx, |attendees| match attendees.clone() {
+            Ok(attendees) => view! {cx, <AttendeeList attendees=attendees />}.into_view(cx),
+            Err(_) => view! {cx,
+                <p class="text-sm text-gray-500">"Could not load who is coming."</p>
+            }
+            .into_view(cx),
+        })
+    };
+
+    let error = move || {
+        respond.value().get().and_then(|result| result.err()).map(|e| view! {cx,
+            <p class="text-sm text-red-600">{e.to_string()}</p>
+        })
+    };
+
+    let input_class = "border border-gray-300 rounded px-2 py-1 text-sm";
+
+    view! {
+        cx,
+        <section class="attendees sm:px-6 pt-4 space-y-3">
+            <h3 class="font-medium text-lg text-orange-600">"Who's coming"</h3>
+            <Suspense fallback=move || view! {cx, <p class="text-sm">"Loading guests..."</p>}>
+                {attendees_view}
+            </Suspense>
+            <ActionForm action=respond>
+                <input type="hidden" name="event_id" value=event_id.to_string() />
+                <div class="grid sm:grid-cols-[2fr,1fr,1fr] gap-2">
+                    <input type="text" name="name" placeholder="Your name" required=true maxlength="60" class=input_class />
+                    <select name="attendance" class=input_class>
+                        <option value="attending">"Attending"</option>
+                        <option value="maybe">"Maybe"</option>
+                        <option value="declined">"Declined"</option>
+                    </select>
+                    <input type="number" name="headcount" value="1" min="1" max="20" title="How many of you" class=input_class />
+                </div>
+                <div class="flex gap-2 pt-2">
+                    <input type="text" name="note" placeholder="Note, such as allergies" maxlength="500" class=format!("{input_class} flex-grow") />
+                    <button type="submit" class="bg-orange-600 hover:bg-orange-700 text-white text-sm rounded px-3 py-1">
+                        "Answer"
+                    </button>
+                </div>
+                {error}
+            </ActionForm>
+        </section>
+    }
+}
+
+#[component]
+fn AttendeeList(cx: Scope, attendees: Attendees) -> impl IntoView {
+    if attendees.rsvps.is_empty() {
+        return view! {cx,
+            <p class="text-sm text-gray-500">"Nobody has answered yet."</p>
+        }
+        .into_view(cx);
+    }
+
+    let summary = [Attendance::Attending, Attendance::Maybe, Attendance::Declined]
+        .into_iter()
+        .map(|attendance| (attendance, attendees.headcount(attendance)))
+        .filter(|(_, headcount)| *headcount > 0)
+        .map(|(attendance, headcount)| format!("{headcount} {}", attendance.label().to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+    view! {
+        cx,
+        <p class="text-sm text-gray-500">{summary}</p>
+        <ul class="space-y-1">
+            {attendees.rsvps.into_iter().map(|rsvp| view! {cx, <AttendeeItem rsvp=rsvp />}).collect::<Vec<_>>()}
+        </ul>
+    }
+    .into_view(cx)
+}
+
+#[component]
+fn AttendeeItem(cx: Scope, rsvp: Rsvp) -> impl IntoView {
+    let status_class = match rsvp.attendance {
+        Attendance::Attending => "text-green-700",
+        Attendance::Maybe => "text-yellow-700",
+        Attendance::Declined => "text-gray-400",
+    };
+    let guests = (rsvp.headcount > 1).then(|| format!(" +{}", rsvp.headcount - 1));
+
+    view! {
+        cx,
+        <li class="text-sm">
+            <span class="font-medium">{rsvp.name}</span>
+            {guests}
+            <span class=format!("pl-2 text-xs {status_class}")>{rsvp.attendance.label()}</span>
+            {rsvp.note.map(|note| view! {cx, <span class="pl-2 text-gray-500">{note}</span>})}
+        </li>
+    }
+}
@@ -0,0 +1,92 @@
+use chrono::{Datelike, NaiveDate};
+use leptos::*;
+
+use crate::calendar::grid;
+use domain::{DayOverview, EventOverview};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// A calendar of whole weeks covering `first..=last`. Days outside the period
+/// are dimmed, and days without meals say so.
+#[component]
+pub fn CalendarGrid(
+    cx: Scope,
+    first: NaiveDate,
+    last: NaiveDate,
+    today: NaiveDate,
+    events: Vec<EventOverview>,
+) -> impl IntoView {
+    let days = DayOverview::group(events);
+
+    let cells = grid(first, last)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|date| {
+            let events = days
+                .iter()
+                .find(|day| day.date == date)
+                .map(|day| day.events.clone())
+                .unwrap_or_default();
+            let in_period = date >= first && date <= last;
+
+            let class = format!(
+                "calendar__day min-h-[6rem] p-2 rounded border {} {}",
+                if date == today {
+                    "border-orange-600"
+                } else {
+                    "border-gray-200"
+                },
+                if in_period { "bg-white" } else { "bg-gray-50 text-gray-400" },
+            );
+
+            view! {
+                cx,
+                <div class=class>
+                    <p class={format!("text-xs font-medium {}", if date == today { "text-orange-600" } else { "text-gray-500" })}>
+                        <span class="sm:hidden">{format!("{} ", date.weekday())}</span>
+                        {date.day()}
+                    </p>
+                    {if events.is_empty() {
+                        view! {cx,
+                            <p class="text-xs text-gray-300 pt-1">"Nothing planned"</p>
+                        }.into_view(cx)
+                    } else {
+                        view! {cx,
+                            <ul class="space-y-1 pt-1">
+                                {events.into_iter().map(|event| view! {cx,
+                                    <li class="text-sm leading-tight">
+                                        <CalendarEvent event=event />
+                                    </li>
+                                }).collect::<Vec<_>>()}
+                            </ul>
+                        }.into_view(cx)
+                    }}
+                </div>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        cx,
+        <div class="calendar grid sm:grid-cols-7 gap-1">
+            {WEEKDAYS.iter().map(|day| view! {cx,
+                <p class="hidden sm:block text-xs font-medium text-gray-500 text-center">{*day}</p>
+            }).collect::<Vec<_>>()}
+            {cells}
+        </div>
+    }
+}
+
+#[component]
+fn CalendarEvent(cx: Scope, event: EventOverview) -> impl IntoView {
+    let time = event
+        .start_time
+        .map(|time| time.format("%H:%M").to_string())
+        .or_else(|| event.meal.map(|meal| meal.label().to_string()));
+
+    view! {
+        cx,
+        {time.map(|time| view! {cx, <span class="text-xs text-gray-500">{time}" "</span>})}
+        <span class="text-orange-600">{event.name}</span>
+    }
+}
@@ -2,18 +2,19 @@ use chrono::Datelike;
 use leptos::*;
 
 use crate::api::events::*;
-use domain::{EventOverview, Image};
+use crate::components::event::{EventArticle, EventArticleProps};
+use domain::EventOverview;
 
 #[component]
 pub fn Day(
     cx: Scope,
-    event: EventOverview,
+    date: chrono::NaiveDate,
+    events: Vec<EventOverview>,
     next: Option<bool>,
     last: Option<bool>,
 ) -> impl IntoView {
-    let (expanded, set_expanded) = create_signal(cx, false);
-    let day = event.time.weekday().to_string();
-    let timestamp = event.time.format("%Y-%m-%d").to_string();
+    let day = date.weekday().to_string();
+    let timestamp = date.format("%Y-%m-%d").to_string();
 
     view! {
         cx,
@@ -39,18 +40,11 @@ pub fn Day(
                     </div>
                 </div>
             </div>
-            <div class="col-start-2 transition-all sm:pb-6">
-                {move || if expanded() == true {
-                    view! {
-                        cx,
-                        <DayContentExpanded event_id=event.id.clone()/>
-                    }.into_view(cx)
-                } else {
-                    view! {
-                        cx,
-                        <DayContentCollapsed event=event.clone() setter=set_expanded />
-                    }.into_view(cx)
-                }}
+            <div class="col-start-2 transition-all sm:pb-6 space-y-6">
+                {events.into_iter().map(|event| view! {
+                    cx,
+                    <Meal event=event />
+                }).collect::<Vec<_>>()}
             {
                last.filter(|l| !l).map(|_l| view! {
                    cx, 
@@ -63,85 +57,62 @@ pub fn Day(
     }
 }
 
+/// A single meal of a day, which can be expanded to show the full event.
 #[component]
-fn DayContentExpanded(cx: Scope, event_id: uuid::Uuid) -> impl IntoView {
-    let full_event = create_resource(cx, move || (), move |_| get_full_event(event_id));
-
-    let image = |cx: Scope, image: Option<Image>| {
-        if let Some(image) = image { 
-            view! {
-                cx, 
-                <img src={image.url} alt=image.alt class="object-cover max-h-[250px] " />
-            }.into_view(cx)
-        } else {
-            view! {cx, <div></div>}.into_view(cx)
-        }
-    };
-
-    let event_view = move || full_event.with(cx, |event| {
-        event.clone().map(|event| {event.map(|event| view! {
-            cx, 
-    <article class="day-content space-x-3 min-h-[150px] flex flex-col">
-        {image(cx, event.cover_image)}
-        <div class="day-content__body space-y-2 pt-6">
-            <h2 class="font-semibold text-xl text-orange-600">{event.name}</h2>
-
-            {
-                event.description.map(|d| view! {cx, 
-                    <p class="font-normal sm:px-6 text">
-                        {d}
-                    </p>
-                    })  
-            }
+fn Meal(cx: Scope, event: EventOverview) -> impl IntoView {
+    let (expanded, set_expanded) = create_signal(cx, false);
+    let schedule = meal_schedule(&event);
 
-            {
-                event.recipe_id.map(|_r| {view! {cx, 
-                    <h3 class="font-medium text-lg pt-2 text-orange-600">"Recipe"</h3>
+    view! {
+        cx,
+        <div class="meal">
+            {schedule.map(|schedule| view! {cx,
+                <p class="text-xs font-medium uppercase tracking-wide text-gray-500 pb-1">{schedule}</p>
+            })}
+            {move || if expanded() == true {
+                view! {
+                    cx,
+                    <DayContentExpanded event_id=event.id.clone()/>
+                }.into_view(cx)
+            } else {
+                view! {
+                    cx,
+                    <DayContentCollapsed event=event.clone() setter=set_expanded />
+                }.into_view(cx)
+            }}
+        </div>
+    }
+}
 
-                    <ol class="px-10">
-                        <li class="list-item list-decimal">
-                            "Lorem ipsum dolor sit amet, qui minim labore adipisicing minim sint cillum sint consectetur
-                            cupidatat."
-                        </li>
-                        <li class="list-item list-decimal">
-                            "Lorem ipsum dolor sit amet, qui minim labore adipisicing minim sint cillum sint consectetur
-                            cupidatat."
-                        </li>
-                    </ol>
+/// Describes when a meal is, e.g. "Dinner · 18:30 · 1 h 30 min".
+fn meal_schedule(event: &EventOverview) -> Option<String> {
+    let duration = event.duration_minutes.map(|minutes| match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes} min"),
+        (hours, 0) => format!("{hours} h"),
+        (hours, minutes) => format!("{hours} h {minutes} min"),
+    });
 
-                    <h3 class="font-medium text-lg pt-2 text-orange-600">"References"</h3>
-                    <ul class="px-10">
-                        <li class="list-item list-decimal">
-                            <a href={r"https://google.com"}>
-                    "Lorem ipsum dolor sit amet, qui minim labore adipisicing minim sint cillum sint consectetur cupidatat"</a>
-                        </li>
-                    </ul>
+    let parts = vec![
+        event.meal.map(|meal| meal.label().to_string()),
+        event.start_time.map(|time| time.format("%H:%M").to_string()),
+        duration,
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
 
-                    <h3 class="font-medium text-lg pt-2 text-orange-600">"Images"</h3>
-                    <div class="day-content__images grid grid-cols-3 gap-4 mx-4 pt-2">
-                        <img
-                            src={r"https://images.unsplash.com/photo-1677856217391-838a585c8290?ixlib=rb-4.0.3&ixid=MnwxMjA3fDB8MHxwaG90by1wYWdlfHx8fGVufDB8fHx8&auto=format&fit=crop&w=2070&q=80"}
-                            alt="no alt text in sight"
-                            class="object-cover"
-                        />
-                        <img
-                            src={r"https://images.unsplash.com/photo-1677856217391-838a585c8290?ixlib=rb-4.0.3&ixid=MnwxMjA3fDB8MHxwaG90by1wYWdlfHx8fGVufDB8fHx8&auto=format&fit=crop&w=2070&q=80"}
-                            alt="no alt text in sight"
-                            class="object-cover"
-                        />
-                        <img
-                            src={r"https://images.unsplash.com/photo-1677856217391-838a585c8290?ixlib=rb-4.0.3&ixid=MnwxMjA3fDB8MHxwaG90by1wYWdlfHx8fGVufDB8fHx8&auto=format&fit=crop&w=2070&q=80"}
-                            alt="no alt text in sight"
-                            class="object-cover"
-                        />
-                    </div>
-                }})     
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
 
-            }
-        </div>
-    </article>
+#[component]
+fn DayContentExpanded(cx: Scope, event_id: uuid::Uuid) -> impl IntoView {
+    let full_event = create_resource(cx, move || (), move |_| get_full_event(cx, event_id));
 
-    <div class="pb-10" />
+    let event_view = move || full_event.with(cx, |event| {
+        event.clone().map(|event| {event.map(|event| view! {
+            cx,
+            <EventArticle event=event />
+            <div class="pb-10" />
         })})
     });
 
@@ -175,7 +146,9 @@ fn DayContentCollapsed(
                     view!{cx, <div></div>}.into_view(cx)
                 }}
         <div class="day-content__body flex flex-col">
-            <h2 class="font-semibold text-lg text-orange-600">{event.name}</h2>
+            <h2 class="font-semibold text-lg text-orange-600">
+                <a href=format!("/events/{}", event.slug) class="hover:underline">{event.name}</a>
+            </h2>
             {if let Some(mut description) = event.description.clone() {
                 description.truncate(120);
                 view! {cx,
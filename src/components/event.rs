@@ -0,0 +1,44 @@
+use leptos::*;
+
+use crate::components::attendees::{AttendeeSection, AttendeeSectionProps};
+use crate::components::recipe::{LinkedRecipe, LinkedRecipeProps};
+use domain::Event;
+
+/// The full event, with its cover image, body, linked recipe and who is
+/// coming.
+#[component]
+pub fn EventArticle(cx: Scope, event: Event) -> impl IntoView {
+    view! {
+        cx,
+        <article class="day-content space-x-3 min-h-[150px] flex flex-col">
+            {event.cover_image.map(|image| view! {cx,
+                <img src={image.url} alt=image.alt class="object-cover max-h-[250px] " />
+            })}
+            <div class="day-content__body space-y-2 pt-6">
+                <h2 class="font-semibold text-xl text-orange-600">{event.name}</h2>
+
+                {
+                    event.description.map(|d| view! {cx,
+                        <p class="font-normal sm:px-6 text">
+                            {d}
+                        </p>
+                    })
+                }
+
+                {
+                    event.body_html.map(|html| view! {cx,
+                        <div class="day-content__article font-normal sm:px-6" inner_html=html />
+                    })
+                }
+
+                {
+                    event.recipe_id.map(|recipe_id| view! {cx,
+                        <LinkedRecipe recipe_id=recipe_id event_id=event.id />
+                    })
+                }
+
+                <AttendeeSection event_id=event.id />
+            </div>
+        </article>
+    }
+}
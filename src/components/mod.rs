@@ -1 +1,5 @@
+pub mod attendees;
+pub mod calendar;
 pub mod day;
+pub mod event;
+pub mod recipe;
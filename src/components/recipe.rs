@@ -0,0 +1,161 @@
+use leptos::*;
+
+use crate::api::recipes::*;
+use domain::units::format_quantity;
+use domain::{Ingredient, Recipe};
+
+/// The recipe of an event, scaled to the servings planned for it. Readers can
+/// change the servings to see the recipe for more or fewer people.
+#[component]
+pub fn LinkedRecipe(cx: Scope, recipe_id: uuid::Uuid, event_id: uuid::Uuid) -> impl IntoView {
+    let recipe = create_resource(
+        cx,
+        move || (),
+        move |_| async move {
+            let recipe = get_recipe(cx, recipe_id).await?;
+            let servings = get_planned_servings(cx, event_id).await?;
+
+            Ok::<_, ServerFnError>(recipe.map(|recipe| (recipe, servings)))
+        },
+    );
+
+    let recipe_view = move || {
+        recipe.with(cx, |recipe| match recipe.clone() {
+            Ok(Some((recipe, servings))) => view! {cx,
+                <ScaledRecipe recipe=recipe servings=servings />
+            }
+            .into_view(cx),
+            Ok(None) => ().into_view(cx),
+            Err(_) => view! {cx,
+                <p class="text-sm text-gray-500">"Could not load the recipe."</p>
+            }
+            .into_view(cx),
+        })
+    };
+
+    view! {
+        cx,
+        <Suspense fallback=move || view! {cx, <p>"Loading recipe..."</p>}>
+            {recipe_view}
+        </Suspense>
+    }
+}
+
+/// Recipes which say how many they feed get a field for the number of people
+/// to cook for, starting at `servings` when it is known.
+#[component]
+fn ScaledRecipe(cx: Scope, recipe: Recipe, servings: Option<u32>) -> impl IntoView {
+    let Some(written_for) = recipe.servings else {
+        return view! {cx, <RecipeView recipe=recipe />}.into_view(cx);
+    };
+
+    let initial = servings.unwrap_or(written_for);
+    let (servings, set_servings) = create_signal(cx, initial);
+
+    view! {
+        cx,
+        <label class="flex items-center gap-2 text-sm text-gray-500 sm:px-6 pt-2">
+            "Cooking for"
+            <input
+                type="number"
+                min="1"
+                max="99"
+                value=initial.to_string()
+                class="border border-gray-300 rounded px-2 py-0.5 w-16"
+                on:input=move |ev| {
+                    if let Some(people) = event_target_value(&ev).parse::<u32>().ok().filter(|p| *p > 0) {
+                        set_servings.set(people);
+                    }
+                }
+            />
+            {format!("people. The recipe is written for {written_for}.")}
+        </label>
+        {move || view! {cx, <RecipeView recipe=recipe.scaled_to(servings.get()) />}}
+    }
+    .into_view(cx)
+}
+
+#[component]
+pub fn RecipeView(cx: Scope, recipe: Recipe) -> impl IntoView {
+    let facts = vec![
+        recipe.servings.map(|s| format!("Serves {s}")),
+        recipe.prep_time_minutes.map(|m| format!("Prep {m} min")),
+        recipe.cook_time_minutes.map(|m| format!("Cook {m} min")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    view! {
+        cx,
+        <h3 class="font-medium text-lg pt-2 text-orange-600">"Recipe"</h3>
+
+        {(!facts.is_empty()).then(|| view! {cx,
+            <p class="text-sm text-gray-500 sm:px-6">{facts.join(" · ")}</p>
+        })}
+
+        {recipe.body_html.clone().map(|html| view! {cx,
+            <div class="day-content__article font-normal sm:px-6" inner_html=html />
+        })}
+
+        {(!recipe.ingredients.is_empty()).then(|| view! {cx,
+            <h4 class="font-medium pt-2 text-orange-600">"Ingredients"</h4>
+            <ul class="px-10">
+                {recipe.ingredients.iter().map(|ingredient| view! {cx,
+                    <li class="list-item list-disc">{format_ingredient(ingredient)}</li>
+                }).collect::<Vec<_>>()}
+            </ul>
+        })}
+
+        {(!recipe.steps.is_empty()).then(|| view! {cx,
+            <h4 class="font-medium pt-2 text-orange-600">"Steps"</h4>
+            <ol class="px-10">
+                {recipe.steps.iter().map(|step| view! {cx,
+                    <li class="list-item list-decimal">{step.text.clone()}</li>
+                }).collect::<Vec<_>>()}
+            </ol>
+        })}
+
+        {(!recipe.references.is_empty()).then(|| view! {cx,
+            <h3 class="font-medium text-lg pt-2 text-orange-600">"References"</h3>
+            <ul class="px-10">
+                {recipe.references.iter().map(|reference| view! {cx,
+                    <li class="list-item list-decimal">
+                        <a href={reference.url.clone()}>
+                            {reference.title.clone().unwrap_or_else(|| reference.url.clone())}
+                        </a>
+                    </li>
+                }).collect::<Vec<_>>()}
+            </ul>
+        })}
+
+        {(!recipe.images.is_empty()).then(|| view! {cx,
+            <h3 class="font-medium text-lg pt-2 text-orange-600">"Images"</h3>
+            <div class="day-content__images grid grid-cols-3 gap-4 mx-4 pt-2">
+                {recipe.images.iter().map(|image| view! {cx,
+                    <img src={image.url.clone()} alt=image.alt.clone() class="object-cover" />
+                }).collect::<Vec<_>>()}
+            </div>
+        })}
+    }
+}
+
+fn format_ingredient(ingredient: &Ingredient) -> String {
+    let amount = match (ingredient.quantity, ingredient.unit.as_ref()) {
+        (Some(quantity), Some(unit)) => Some(format!("{} {unit}", format_quantity(quantity))),
+        (Some(quantity), None) => Some(format_quantity(quantity)),
+        (None, Some(unit)) => Some(unit.clone()),
+        (None, None) => None,
+    };
+
+    let mut line = match amount {
+        Some(amount) => format!("{amount} {}", ingredient.name),
+        None => ingredient.name.clone(),
+    };
+
+    if let Some(note) = &ingredient.note {
+        line = format!("{line}, {note}");
+    }
+
+    line
+}
@@ -0,0 +1,272 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use serde::Deserialize;
+use services::{Author, ContentConfig};
+
+use crate::app::Analytics;
+
+const DEFAULT_CONFIG_FILE: &str = "bitebuds.yaml";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("could not read config file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("could not parse config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+    #[error("invalid config: {0}")]
+    Invalid(String),
+}
+
+/// Command line flags. Every flag can also be set through its environment
+/// variable, and takes precedence over the config file.
+#[derive(Debug, Default, Parser)]
+#[command(name = "bitebuds")]
+struct Cli {
+    /// Path to a yaml config file [default: bitebuds.yaml, if it exists]
+    #[arg(long, env = "BITEBUDS_CONFIG")]
+    config: Option<PathBuf>,
+    /// Git repository to sync content from
+    #[arg(long, env = "BITE_ARTICLE_REPO_URL")]
+    repo_url: Option<String>,
+    /// Branch of the git repository to read content from
+    #[arg(long, env = "BITE_ARTICLE_REPO_BRANCH")]
+    branch: Option<String>,
+    /// Root of the content on the local filesystem
+    #[arg(long, env = "BITE_CONTENT_PATH")]
+    content_path: Option<PathBuf>,
+    /// Directory of the events, relative to the content root
+    #[arg(long, env = "BITE_ARTICLE_REPO_PATH")]
+    events_dir: Option<PathBuf>,
+    /// Directory of the recipes, relative to the content root
+    #[arg(long, env = "BITE_RECIPE_REPO_PATH")]
+    recipes_dir: Option<PathBuf>,
+    /// Seconds between syncs of the content repository
+    #[arg(long, env = "BITE_SYNC_INTERVAL_SECS")]
+    sync_interval_secs: Option<u64>,
+    /// SQLite database to keep content and app data in
+    #[arg(long, env = "BITE_DATABASE_PATH")]
+    database_path: Option<PathBuf>,
+    /// Timezone of the household, e.g. Europe/Copenhagen
+    #[arg(long, env = "BITE_TIMEZONE")]
+    timezone: Option<String>,
+    /// Public url of the site, used in links from feeds
+    #[arg(long, env = "BITE_SITE_URL")]
+    site_url: Option<String>,
+    /// Name of an author allowed to log in, in addition to those in the config file
+    #[arg(long, env = "BITE_AUTHOR_NAME")]
+    author_name: Option<String>,
+    /// Argon2 hash of the author's password, from `biteme hash-password`
+    #[arg(long, env = "BITE_AUTHOR_PASSWORD_HASH")]
+    author_password_hash: Option<String>,
+    /// Domain reported to plausible analytics
+    #[arg(long, env = "BITE_ANALYTICS_DOMAIN")]
+    analytics_domain: Option<String>,
+    /// Url of the plausible analytics script
+    #[arg(long, env = "BITE_ANALYTICS_SCRIPT")]
+    analytics_script: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct FileConfig {
+    #[serde(default)]
+    content: FileContentConfig,
+    database: Option<FileDatabaseConfig>,
+    timezone: Option<String>,
+    site_url: Option<String>,
+    #[serde(default)]
+    authors: Vec<FileAuthorConfig>,
+    analytics: Option<FileAnalyticsConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct FileContentConfig {
+    repo_url: Option<String>,
+    branch: Option<String>,
+    path: Option<PathBuf>,
+    events_dir: Option<PathBuf>,
+    recipes_dir: Option<PathBuf>,
+    sync_interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct FileDatabaseConfig {
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct FileAuthorConfig {
+    name: String,
+    password_hash: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct FileAnalyticsConfig {
+    domain: Option<String>,
+    script: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub content: ContentConfig,
+    /// Content is kept in memory only when no database is configured.
+    pub database: Option<PathBuf>,
+    /// When not set, links from feeds are based on the host of the request.
+    pub site_url: Option<String>,
+    /// Who may log in to change the plan. Nobody can when empty.
+    pub authors: Vec<Author>,
+    pub analytics: Option<Analytics>,
+}
+
+impl Config {
+    /// Loads the config from defaults, then the config file, then environment
+    /// variables and command line flags, each overriding the one before.
+    pub fn load() -> Result<Self, ConfigError> {
+        let cli = Cli::parse();
+
+        let file = match &cli.config {
+            Some(path) => read_file(path)?,
+            None if PathBuf::from(DEFAULT_CONFIG_FILE).exists() => {
+                read_file(&PathBuf::from(DEFAULT_CONFIG_FILE))?
+            }
+            None => FileConfig::default(),
+        };
+
+        Self::merge(cli, file)
+    }
+
+    fn merge(cli: Cli, file: FileConfig) -> Result<Self, ConfigError> {
+        let defaults = ContentConfig::default();
+
+        let sync_interval_secs = cli
+            .sync_interval_secs
+            .or(file.content.sync_interval_secs)
+            .unwrap_or(defaults.sync_interval.as_secs());
+        if sync_interval_secs == 0 {
+            return Err(ConfigError::Invalid(
+                "sync interval must be at least one second".into(),
+            ));
+        }
+
+        let timezone = match non_empty(cli.timezone).or(file.timezone) {
+            Some(timezone) => timezone
+                .parse::<chrono_tz::Tz>()
+                .map_err(|e| ConfigError::Invalid(format!("unknown timezone {timezone}: {e}")))?,
+            None => defaults.timezone,
+        };
+
+        let content = ContentConfig {
+            repo_url: non_empty(cli.repo_url).or(file.content.repo_url),
+            branch: non_empty(cli.branch).or(file.content.branch),
+            path: cli
+                .content_path
+                .or(file.content.path)
+                .unwrap_or(defaults.path),
+            events_dir: cli
+                .events_dir
+                .or(file.content.events_dir)
+                .unwrap_or(defaults.events_dir),
+            recipes_dir: cli.recipes_dir.or(file.content.recipes_dir),
+            sync_interval: Duration::from_secs(sync_interval_secs),
+            timezone,
+        };
+
+        if content.events_dir.is_absolute() {
+            return Err(ConfigError::Invalid(
+                "events directory must be relative to the content root".into(),
+            ));
+        }
+        if content.repo_url.is_none() && !content.path.is_dir() {
+            return Err(ConfigError::Invalid(format!(
+                "no repository url is set, and the content path {} is not a directory",
+                content.path.display()
+            )));
+        }
+
+        let database = cli
+            .database_path
+            .or(file.database.and_then(|database| database.path));
+
+        let site_url = non_empty(cli.site_url)
+            .or(file.site_url)
+            .map(|url| url.trim_end_matches('/').to_string());
+
+        let mut authors = file
+            .authors
+            .into_iter()
+            .map(|author| (author.name, author.password_hash))
+            .collect::<Vec<_>>();
+        match (
+            non_empty(cli.author_name),
+            non_empty(cli.author_password_hash),
+        ) {
+            (Some(name), Some(password_hash)) => {
+                authors.retain(|(existing, _)| !existing.eq_ignore_ascii_case(&name));
+                authors.push((name, password_hash));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(ConfigError::Invalid(
+                    "an author needs both a name and a password hash".into(),
+                ))
+            }
+        }
+        let authors = authors
+            .into_iter()
+            .map(|(name, password_hash)| {
+                Author::new(name, password_hash).map_err(|e| ConfigError::Invalid(e.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let file_analytics = file.analytics.unwrap_or_default();
+        let analytics = match (
+            non_empty(cli.analytics_domain).or(file_analytics.domain),
+            non_empty(cli.analytics_script).or(file_analytics.script),
+        ) {
+            (Some(domain), Some(script_src)) => Some(Analytics { domain, script_src }),
+            (None, None) => None,
+            _ => {
+                return Err(ConfigError::Invalid(
+                    "analytics needs both a domain and a script".into(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            content,
+            database,
+            site_url,
+            authors,
+            analytics,
+        })
+    }
+}
+
+fn read_file(path: &PathBuf) -> Result<FileConfig, ConfigError> {
+    let contents = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+        path: path.clone(),
+        source,
+    })?;
+
+    serde_yaml::from_str(&contents).map_err(|source| ConfigError::Parse {
+        path: path.clone(),
+        source,
+    })
+}
+
+/// Empty environment variables are treated as unset.
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.is_empty())
+}
@@ -13,8 +13,9 @@ cfg_if! { if #[cfg(feature = "ssr")] {
     use std::sync::Arc;
     use leptos::{LeptosOptions, Errors, view};
     use crate::app::{App, AppProps};
+    use crate::state::AppState;
 
-    pub async fn file_and_error_handler(uri: Uri, Extension(options): Extension<Arc<LeptosOptions>>, req: Request<Body>) -> AxumResponse {
+    pub async fn file_and_error_handler(uri: Uri, Extension(options): Extension<Arc<LeptosOptions>>, Extension(state): Extension<AppState>, req: Request<Body>) -> AxumResponse {
         let options = &*options;
         let root = options.site_root.clone();
         let res = get_static_file(uri.clone(), &root).await.unwrap();
@@ -22,8 +23,9 @@ cfg_if! { if #[cfg(feature = "ssr")] {
         if res.status() == StatusCode::OK {
            res.into_response()
         } else{
-            let handler = leptos_axum::render_app_to_stream(
+            let handler = leptos_axum::render_app_to_stream_with_context(
                 options.to_owned(),
+                move |cx| state.provide(cx),
                 move |cx| view!{ cx, <App/> }
             );
             handler(req).await.into_response()
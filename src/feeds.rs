@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Extension, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Deserialize;
+use services::feed::FeedEntry;
+
+use crate::state::{site_url, AppState};
+
+/// How far back the calendar feed goes when no range is given.
+const CALENDAR_HISTORY_WEEKS: i64 = 4;
+
+/// Longest range the calendar feed can be asked for, as recurring events are
+/// expanded for every day of it.
+const MAX_CALENDAR_DAYS: i64 = 2 * 366;
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarQuery {
+    from: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    tag: Option<String>,
+}
+
+/// The meal plan as an iCalendar feed, for subscribing to from a calendar app.
+pub async fn calendar_handler(
+    Extension(state): Extension<AppState>,
+    Query(query): Query<CalendarQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let today = state.store.today();
+    let from = query
+        .from
+        .unwrap_or(today - Duration::weeks(CALENDAR_HISTORY_WEEKS));
+    let until = query
+        .until
+        .unwrap_or(today + Duration::weeks(services::RECURRENCE_WINDOW_WEEKS));
+
+    if until < from {
+        return (StatusCode::BAD_REQUEST, "until is before from").into_response();
+    }
+    if until - from > Duration::days(MAX_CALENDAR_DAYS) {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("the range can be at most {MAX_CALENDAR_DAYS} days"),
+        )
+            .into_response();
+    }
+
+    let events = match state.store.get_events_between(from, until).await {
+        Ok(events) => events,
+        Err(e) => {
+            tracing::warn!(error = e.to_string(), "could not list events for calendar");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let events = match &query.tag {
+        Some(tag) => events
+            .into_iter()
+            .filter(|event| event.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            .collect(),
+        None => events,
+    };
+
+    let body = services::ical::calendar(
+        &events,
+        state.store.timezone(),
+        &site_url(state.site_url.as_deref(), &headers),
+        Utc::now(),
+    );
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "inline; filename=\"bitebuds.ics\"",
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Newly planned meals as an Atom feed.
+pub async fn atom_handler(Extension(state): Extension<AppState>, headers: HeaderMap) -> Response {
+    feed_response(
+        &state,
+        &headers,
+        "application/atom+xml; charset=utf-8",
+        services::feed::atom,
+    )
+    .await
+}
+
+/// Newly planned meals as an RSS feed.
+pub async fn rss_handler(Extension(state): Extension<AppState>, headers: HeaderMap) -> Response {
+    feed_response(
+        &state,
+        &headers,
+        "application/rss+xml; charset=utf-8",
+        services::feed::rss,
+    )
+    .await
+}
+
+async fn feed_response(
+    state: &AppState,
+    headers: &HeaderMap,
+    content_type: &'static str,
+    render: fn(&[FeedEntry], &str, DateTime<Utc>) -> String,
+) -> Response {
+    let entries = match state.store.get_recently_added_events().await {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!(error = e.to_string(), "could not list events for feed");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let body = render(&entries, &site_url(state.site_url.as_deref(), headers), Utc::now());
+
+    ([(header::CONTENT_TYPE, content_type)], body).into_response()
+}
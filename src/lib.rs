@@ -2,9 +2,20 @@
 
 pub mod api;
 pub mod app;
+#[cfg(feature = "ssr")]
+pub mod auth;
+mod calendar;
 mod components;
+#[cfg(feature = "ssr")]
+pub mod config;
 pub mod fallback;
+#[cfg(feature = "ssr")]
+pub mod feeds;
 mod pages;
+#[cfg(feature = "ssr")]
+pub mod rest;
+#[cfg(feature = "ssr")]
+pub mod state;
 use cfg_if::cfg_if;
 
 cfg_if! {
@@ -1,25 +1,64 @@
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() {
-    use axum::{extract::Extension, routing::post, Router};
+    use axum::{
+        extract::Extension,
+        routing::{get, post},
+        Router,
+    };
     use leptos::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
     use ssr_modes::app::*;
+    use ssr_modes::config::Config;
     use ssr_modes::fallback::file_and_error_handler;
+    use ssr_modes::feeds::{atom_handler, calendar_handler, rss_handler};
+    use ssr_modes::rest;
+    use ssr_modes::state::{server_fn_handler, AppState};
+    use services::{Auth, EventStore};
     use std::sync::Arc;
     use tracing_subscriber::EnvFilter;
 
-    std::env::set_var(
-        "BITE_ARTICLE_REPO_URL",
-        "git@git.front.kjuulh.io:kjuulh/obsidian.git",
-    );
-    std::env::set_var("BITE_ARTICLE_REPO_PATH", "areas/food/events");
-
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
-    ssr_modes::api::events::boostrap().await.unwrap();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!(error = e.to_string(), "invalid configuration");
+            std::process::exit(1);
+        }
+    };
+
+    let store = match &config.database {
+        Some(path) => match EventStore::with_database(config.content.clone(), path).await {
+            Ok(store) => store,
+            Err(e) => {
+                tracing::error!(error = e.to_string(), "could not open database");
+                std::process::exit(1);
+            }
+        },
+        None => EventStore::new(config.content.clone()),
+    };
+    if let Err(e) = store.bootstrap().await {
+        tracing::error!(error = e.to_string(), "could not load content");
+        std::process::exit(1);
+    }
+
+    let auth = match Auth::new(config.authors.clone(), store.sessions()) {
+        Ok(auth) => auth,
+        Err(e) => {
+            tracing::error!(error = e.to_string(), "could not set up authentication");
+            std::process::exit(1);
+        }
+    };
+
+    let state = AppState {
+        store,
+        auth,
+        analytics: config.analytics.clone(),
+        site_url: config.site_url.clone(),
+    };
 
     let conf = get_configuration(None).await.unwrap();
     let addr = conf.leptos_options.site_addr;
@@ -29,11 +68,25 @@ async fn main() {
 
     ssr_modes::api::register();
 
+    let routes_state = state.clone();
     let app = Router::new()
-        .route("/api/*fn_name", post(leptos_axum::handle_server_fns))
-        .leptos_routes(leptos_options.clone(), routes, |cx| view! { cx, <App/> })
+        .route("/api/v1/events", get(rest::list_events))
+        .route("/api/v1/events/:id", get(rest::get_event))
+        .route("/api/v1/recipes/:id", get(rest::get_recipe))
+        .route("/api/v1/openapi.json", get(rest::openapi))
+        .route("/api/*fn_name", post(server_fn_handler))
+        .route("/calendar.ics", get(calendar_handler))
+        .route("/feed.xml", get(atom_handler))
+        .route("/rss.xml", get(rss_handler))
+        .leptos_routes_with_context(
+            leptos_options.clone(),
+            routes,
+            move |cx| routes_state.provide(cx),
+            |cx| view! { cx, <App/> },
+        )
         .fallback(file_and_error_handler)
-        .layer(Extension(Arc::new(leptos_options)));
+        .layer(Extension(Arc::new(leptos_options)))
+        .layer(Extension(state));
 
     // run our app with hyper
     // `axum::Server` is a re-export of `hyper::Server`
@@ -0,0 +1,117 @@
+use leptos::*;
+use leptos_router::*;
+
+use crate::api;
+use crate::components::day::{Day, DayProps};
+use domain::DayOverview;
+
+#[component]
+pub fn ArchivePage(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let query = use_query_map(cx);
+
+    let archive = create_resource(
+        cx,
+        move || {
+            (
+                params.with(|p| p.get("month").cloned()),
+                query.with(|q| q.get("cursor").cloned()),
+            )
+        },
+        move |(month, cursor)| async move { api::events::get_archive(cx, month, cursor).await },
+    );
+
+    let archive_view = move || {
+        archive.with(cx, |archive| match archive.clone() {
+            Ok(archive) => {
+                let month = archive.month;
+                // The first and last months there are link to themselves.
+                let previous_month = month
+                    .checked_sub_months(chrono::Months::new(1))
+                    .unwrap_or(month);
+                let next_month = month
+                    .checked_add_months(chrono::Months::new(1))
+                    .unwrap_or(month);
+
+                let mut days = DayOverview::group(archive.page.events);
+                days.reverse();
+                let day_count = days.len();
+
+                view! {
+                    cx,
+                    <div class="flex justify-between items-baseline pb-6">
+                        <a class="text-sm text-gray-500 hover:text-orange-600" href=format!("/archive/{}", previous_month.format("%Y-%m"))>
+                            {format!("← {}", previous_month.format("%B %Y"))}
+                        </a>
+                        <h2 class="font-semibold text-lg text-gray-700">{month.format("%B %Y").to_string()}</h2>
+                        {if archive.has_next_month {
+                            view! {cx,
+                                <a class="text-sm text-gray-500 hover:text-orange-600" href=format!("/archive/{}", next_month.format("%Y-%m"))>
+                                    {format!("{} →", next_month.format("%B %Y"))}
+                                </a>
+                            }.into_view(cx)
+                        } else {
+                            view! {cx, <span></span>}.into_view(cx)
+                        }}
+                    </div>
+
+                    {if days.is_empty() {
+                        view! {cx,
+                            <p class="text-gray-500">"Nothing was eaten this month, or at least nothing was written down."</p>
+                        }.into_view(cx)
+                    } else {
+                        view! {cx,
+                            <ul class="days flex flex-col">
+                                {days.into_iter().enumerate().map(|(index, day)| view! {
+                                    cx,
+                                    <Day
+                                        date=day.date
+                                        events=day.events
+                                        next=None
+                                        last={(index == day_count - 1).then_some(true)}
+                                    />
+                                }).collect::<Vec<_>>()}
+                            </ul>
+                        }.into_view(cx)
+                    }}
+
+                    {archive.page.next_cursor.map(|cursor| view! {cx,
+                        <a
+                            class="block text-center text-sm text-gray-500 hover:text-orange-600 pt-6"
+                            href=format!("/archive/{}?cursor={}", month.format("%Y-%m"), encode_query(&cursor))
+                        >
+                            "Older meals"
+                        </a>
+                    })}
+                }.into_view(cx)
+            }
+            Err(_) => view! {cx,
+                <p class="text-gray-500">"There is no archive for that month."</p>
+            }.into_view(cx),
+        })
+    };
+
+    view! {
+        cx,
+        <div class="space-y-4 pt-8">
+            <Suspense fallback=move || view! {cx, <p>"Loading archive..."</p>}>
+                {archive_view}
+            </Suspense>
+        </div>
+    }
+}
+
+/// Cursors contain `|` and `:`, which are escaped to keep the link valid.
+fn encode_query(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c.to_string(),
+            c => c
+                .to_string()
+                .bytes()
+                .map(|b| format!("%{b:02X}"))
+                .collect(),
+        })
+        .collect()
+}
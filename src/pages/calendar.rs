@@ -0,0 +1,149 @@
+use chrono::Datelike;
+use leptos::*;
+use leptos_router::*;
+
+use crate::api;
+use crate::calendar::{format_month, format_week};
+use crate::components::calendar::{CalendarGrid, CalendarGridProps};
+
+#[component]
+pub fn WeekPage(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+
+    let calendar = create_resource(
+        cx,
+        move || params.with(|p| p.get("week").cloned()),
+        move |week| async move { api::events::get_week(cx, week).await },
+    );
+
+    let calendar_view = move || {
+        calendar.with(cx, |calendar| match calendar.clone() {
+            Ok(calendar) => {
+                // The first and last weeks there are link to themselves.
+                let previous = calendar
+                    .first
+                    .checked_sub_signed(chrono::Duration::weeks(1))
+                    .unwrap_or(calendar.first);
+                let next = calendar
+                    .first
+                    .checked_add_signed(chrono::Duration::weeks(1))
+                    .unwrap_or(calendar.first);
+
+                view! {
+                    cx,
+                    <CalendarNav
+                        previous_href=format!("/week/{}", format_week(previous))
+                        previous_label=format!("Week {}", previous.iso_week().week())
+                        title=format!(
+                            "Week {}, {}",
+                            calendar.first.iso_week().week(),
+                            calendar.first.iso_week().year()
+                        )
+                        next_href=format!("/week/{}", format_week(next))
+                        next_label=format!("Week {}", next.iso_week().week())
+                    />
+                    <CalendarGrid
+                        first=calendar.first
+                        last=calendar.last
+                        today=calendar.today
+                        events=calendar.events
+                    />
+                }
+                .into_view(cx)
+            }
+            Err(_) => view! {cx,
+                <p class="text-gray-500">"There is no such week."</p>
+            }
+            .into_view(cx),
+        })
+    };
+
+    view! {
+        cx,
+        <div class="space-y-4 pt-8">
+            <Suspense fallback=move || view! {cx, <p>"Loading week..."</p>}>
+                {calendar_view}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+pub fn MonthPage(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+
+    let calendar = create_resource(
+        cx,
+        move || params.with(|p| p.get("month").cloned()),
+        move |month| async move { api::events::get_month(cx, month).await },
+    );
+
+    let calendar_view = move || {
+        calendar.with(cx, |calendar| match calendar.clone() {
+            Ok(calendar) => {
+                let previous = calendar
+                    .first
+                    .checked_sub_months(chrono::Months::new(1))
+                    .unwrap_or(calendar.first);
+                let next = calendar
+                    .first
+                    .checked_add_months(chrono::Months::new(1))
+                    .unwrap_or(calendar.first);
+
+                view! {
+                    cx,
+                    <CalendarNav
+                        previous_href=format!("/month/{}", format_month(previous))
+                        previous_label=previous.format("%B %Y").to_string()
+                        title=calendar.first.format("%B %Y").to_string()
+                        next_href=format!("/month/{}", format_month(next))
+                        next_label=next.format("%B %Y").to_string()
+                    />
+                    <CalendarGrid
+                        first=calendar.first
+                        last=calendar.last
+                        today=calendar.today
+                        events=calendar.events
+                    />
+                }
+                .into_view(cx)
+            }
+            Err(_) => view! {cx,
+                <p class="text-gray-500">"There is no such month."</p>
+            }
+            .into_view(cx),
+        })
+    };
+
+    view! {
+        cx,
+        <div class="space-y-4 pt-8">
+            <Suspense fallback=move || view! {cx, <p>"Loading month..."</p>}>
+                {calendar_view}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn CalendarNav(
+    cx: Scope,
+    previous_href: String,
+    previous_label: String,
+    title: String,
+    next_href: String,
+    next_label: String,
+) -> impl IntoView {
+    view! {
+        cx,
+        <div class="flex justify-between items-baseline pb-2">
+            <a class="text-sm text-gray-500 hover:text-orange-600" href=previous_href>
+                {format!("← {previous_label}")}
+            </a>
+            <h2 class="font-semibold text-lg text-gray-700">{title}</h2>
+            <a class="text-sm text-gray-500 hover:text-orange-600" href=next_href>
+                {format!("{next_label} →")}
+            </a>
+        </div>
+    }
+}
@@ -0,0 +1,240 @@
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+
+use crate::api::auth::get_session;
+use crate::api::editor::*;
+use domain::{AuthorSession, EditableEvent, EventDraft, MealSlot};
+
+/// Where authors plan a new meal.
+#[component]
+pub fn NewEventPage(cx: Scope) -> impl IntoView {
+    let session = create_resource(cx, || (), move |_| get_session(cx));
+
+    let form_view = move || {
+        session.with(cx, |session| match session.clone() {
+            Ok(Some(session)) => view! {cx, <EventForm session=session event=None />}.into_view(cx),
+            Ok(None) => view! {cx, <LoginNeeded />}.into_view(cx),
+            Err(_) => view! {cx,
+                <p class="text-gray-500">"Could not check whether you are logged in."</p>
+            }
+            .into_view(cx),
+        })
+    };
+
+    view! {
+        cx,
+        <Title text="Plan a meal · Bitebuds" />
+        <div class="space-y-4 pt-8">
+            <h2 class="font-semibold text-lg">"Plan a meal"</h2>
+            <Suspense fallback=move || view! {cx, <p>"Loading..."</p>}>
+                {form_view}
+            </Suspense>
+        </div>
+    }
+}
+
+/// Where authors change or remove a meal. Occurrences of recurring meals edit
+/// the meal they come from.
+#[component]
+pub fn EditEventPage(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+
+    let editable = create_resource(
+        cx,
+        move || params.with(|p| p.get("slug").cloned().unwrap_or_default()),
+        move |slug| async move {
+            let Some(session) = get_session(cx).await? else {
+                return Ok(None);
+            };
+            let event = get_editable_event(cx, slug).await?;
+
+            Ok::<_, ServerFnError>(Some((session, event)))
+        },
+    );
+
+    let form_view = move || {
+        editable.with(cx, |editable| match editable.clone() {
+            Ok(Some((session, Some(event)))) => view! {cx,
+                <EventForm session=session.clone() event=Some(event.clone()) />
+                <DeleteEventForm session=session event=event />
+            }
+            .into_view(cx),
+            Ok(Some((_, None))) => view! {cx,
+                <p class="text-gray-500">"There is no such meal."</p>
+            }
+            .into_view(cx),
+            Ok(None) => view! {cx, <LoginNeeded />}.into_view(cx),
+            Err(e) => view! {cx,
+                <p class="text-gray-500">{e.to_string()}</p>
+            }
+            .into_view(cx),
+        })
+    };
+
+    view! {
+        cx,
+        <Title text="Edit meal · Bitebuds" />
+        <div class="space-y-4 pt-8">
+            <h2 class="font-semibold text-lg">"Edit meal"</h2>
+            <Suspense fallback=move || view! {cx, <p>"Loading..."</p>}>
+                {form_view}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn LoginNeeded(cx: Scope) -> impl IntoView {
+    view! {
+        cx,
+        <p class="text-gray-500">
+            "Only authors can change the plan. "
+            <a href="/login" class="underline">"Log in"</a>
+        </p>
+    }
+}
+
+/// Saving commits the meal to the content repository, and shows it once it
+/// is published.
+#[component]
+fn EventForm(cx: Scope, session: AuthorSession, event: Option<EditableEvent>) -> impl IntoView {
+    let save = create_server_action::<SaveEvent>(cx);
+    let navigate = use_navigate(cx);
+
+    create_effect(cx, move |_| {
+        if let Some(Ok(slug)) = save.value().get() {
+            _ = navigate(&format!("/events/{slug}"), Default::default());
+        }
+    });
+
+    let error = move || {
+        save.value().get().and_then(|result| result.err()).map(|e| view! {cx,
+            <p class="text-sm text-red-600">{e.to_string()}</p>
+        })
+    };
+
+    let slug = event.as_ref().map(|e| e.slug.clone()).unwrap_or_default();
+    let base_commit = event.as_ref().and_then(|e| e.base_commit.clone()).unwrap_or_default();
+    let draft = event.map(|e| e.draft);
+    let field = |f: fn(&EventDraft) -> Option<String>| draft.as_ref().and_then(f).unwrap_or_default();
+
+    let name = field(|d| Some(d.name.clone()));
+    let description = field(|d| d.description.clone());
+    let date = field(|d| Some(d.date.format("%Y-%m-%d").to_string()));
+    let start_time = field(|d| d.start_time.map(|t| t.format("%H:%M").to_string()));
+    let servings = field(|d| d.servings.map(|s| s.to_string()));
+    let tags = field(|d| Some(d.tags.join(", ")));
+    let recipe = field(|d| d.recipe.clone());
+    let cover_image_url = field(|d| d.cover_image_url.clone());
+    let cover_image_alt = field(|d| d.cover_image_alt.clone());
+    let body = field(|d| Some(d.body.clone()));
+    let meal = draft.as_ref().and_then(|d| d.meal);
+
+    let input_class = "w-full border border-gray-300 rounded px-2 py-1 text-sm";
+    let label_class = "block text-sm text-gray-600 space-y-1";
+
+    view! {
+        cx,
+        <ActionForm action=save>
+            <input type="hidden" name="csrf_token" value=session.csrf_token />
+            <input type="hidden" name="slug" value=slug />
+            <input type="hidden" name="base_commit" value=base_commit />
+            <div class="space-y-3">
+                <label class=label_class>
+                    <span>"Name"</span>
+                    <input type="text" name="name" value=name required=true maxlength="120" class=input_class />
+                </label>
+                <label class=label_class>
+                    <span>"Description"</span>
+                    <input type="text" name="description" value=description class=input_class />
+                </label>
+                <div class="grid sm:grid-cols-4 gap-2">
+                    <label class=label_class>
+                        <span>"Date"</span>
+                        <input type="date" name="date" value=date required=true class=input_class />
+                    </label>
+                    <label class=label_class>
+                        <span>"Time"</span>
+                        <input type="time" name="start_time" value=start_time class=input_class />
+                    </label>
+                    <label class=label_class>
+                        <span>"Meal"</span>
+                        <select name="meal" class=input_class>
+                            <option value="" selected=meal.is_none()>"-"</option>
+                            {[MealSlot::Breakfast, MealSlot::Lunch, MealSlot::Dinner, MealSlot::Snack]
+                                .into_iter()
+                                .map(|slot| view! {cx,
+                                    <option value=slot.label().to_lowercase() selected=meal == Some(slot)>
+                                        {slot.label()}
+                                    </option>
+                                })
+                                .collect::<Vec<_>>()}
+                        </select>
+                    </label>
+                    <label class=label_class>
+                        <span>"Servings"</span>
+                        <input type="number" name="servings" value=servings min="1" class=input_class />
+                    </label>
+                </div>
+                <label class=label_class>
+                    <span>"Tags, separated by commas"</span>
+                    <input type="text" name="tags" value=tags class=input_class />
+                </label>
+                <label class=label_class>
+                    <span>"Recipe"</span>
+                    <input type="text" name="recipe" value=recipe placeholder="Slug of the recipe" class=input_class />
+                </label>
+                <div class="grid sm:grid-cols-2 gap-2">
+                    <label class=label_class>
+                        <span>"Cover image"</span>
+                        <input type="url" name="cover_image_url" value=cover_image_url class=input_class />
+                    </label>
+                    <label class=label_class>
+                        <span>"Description of the image"</span>
+                        <input type="text" name="cover_image_alt" value=cover_image_alt class=input_class />
+                    </label>
+                </div>
+                <label class=label_class>
+                    <span>"Article, in markdown"</span>
+                    <textarea name="body" rows="12" class=format!("{input_class} font-mono")>{body}</textarea>
+                </label>
+                <button type="submit" class="bg-orange-600 hover:bg-orange-700 text-white text-sm rounded px-3 py-1">
+                    "Save and publish"
+                </button>
+                {error}
+            </div>
+        </ActionForm>
+    }
+}
+
+#[component]
+fn DeleteEventForm(cx: Scope, session: AuthorSession, event: EditableEvent) -> impl IntoView {
+    let delete = create_server_action::<DeleteEvent>(cx);
+    let navigate = use_navigate(cx);
+
+    create_effect(cx, move |_| {
+        if let Some(Ok(())) = delete.value().get() {
+            _ = navigate("/", Default::default());
+        }
+    });
+
+    let error = move || {
+        delete.value().get().and_then(|result| result.err()).map(|e| view! {cx,
+            <p class="text-sm text-red-600">{e.to_string()}</p>
+        })
+    };
+
+    view! {
+        cx,
+        <ActionForm action=delete>
+            <input type="hidden" name="csrf_token" value=session.csrf_token />
+            <input type="hidden" name="slug" value=event.slug />
+            <input type="hidden" name="base_commit" value=event.base_commit.unwrap_or_default() />
+            <button type="submit" class="text-sm text-red-600 hover:text-red-800 underline">
+                "Remove this meal"
+            </button>
+            {error}
+        </ActionForm>
+    }
+}
@@ -0,0 +1,145 @@
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+
+use crate::api;
+use crate::api::auth::get_session;
+use crate::api::site::get_site_url;
+use crate::components::event::{EventArticle, EventArticleProps};
+use domain::Event;
+
+/// A single event on its own page, so it can be shared. The route is rendered
+/// with `SsrMode::Async`, so the title, meta tags and status code are known
+/// before the response is sent.
+#[component]
+pub fn EventDetailPage(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+
+    let event = create_resource(
+        cx,
+        move || params.with(|p| p.get("slug").cloned().unwrap_or_default()),
+        move |slug| async move {
+            let event = api::events::get_event_by_slug(cx, slug).await?;
+            let site_url = get_site_url(cx).await?;
+
+            Ok::<_, ServerFnError>(event.map(|event| (event, site_url)))
+        },
+    );
+
+    let event_view = move || {
+        event.with(cx, |event| match event.clone() {
+            Ok(Some((event, site_url))) => view! {
+                cx,
+                <EventMeta event=event.clone() site_url=site_url />
+                <EditLink slug=event.slug.clone() />
+                <EventArticle event=event />
+            }
+            .into_view(cx),
+            Ok(None) => {
+                not_found(cx);
+
+                view! {cx,
+                    <Title text="Not found · Bitebuds" />
+                    <p class="text-gray-500">"There is no such meal."</p>
+                }
+                .into_view(cx)
+            }
+            Err(_) => view! {cx,
+                <p class="text-gray-500">"The meal could not be loaded."</p>
+            }
+            .into_view(cx),
+        })
+    };
+
+    view! {
+        cx,
+        <div class="space-y-4 pt-8">
+            <Suspense fallback=move || view! {cx, <p>"Loading meal..."</p>}>
+                {event_view}
+            </Suspense>
+        </div>
+    }
+}
+
+/// Title, OpenGraph and Twitter card tags for link previews, which need
+/// absolute urls.
+#[component]
+fn EventMeta(cx: Scope, event: Event, site_url: String) -> impl IntoView {
+    let title = format!("{} · Bitebuds", event.name);
+    let description = event
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("{}, {}", event.name, event.time.format("%A %-d %B %Y")));
+    let url = absolute_url(&site_url, &format!("/events/{}", event.slug));
+    let image = event
+        .cover_image
+        .map(|image| absolute_url(&site_url, &image.url));
+    let card = if image.is_some() {
+        "summary_large_image"
+    } else {
+        "summary"
+    };
+
+    view! {
+        cx,
+        <Title text=title.clone() />
+        <Meta name="description" content=description.clone() />
+        <Meta property="og:type" content="article" />
+        <Meta property="og:site_name" content="Bitebuds" />
+        <Meta property="og:url" content=url />
+        <Meta property="og:title" content=event.name.clone() />
+        <Meta property="og:description" content=description.clone() />
+        <Meta name="twitter:card" content=card />
+        <Meta name="twitter:title" content=event.name />
+        <Meta name="twitter:description" content=description />
+        {image.map(|image| view! {cx,
+            <Meta property="og:image" content=image.clone() />
+            <Meta name="twitter:image" content=image />
+        })}
+    }
+}
+
+/// Only shown to authors.
+#[component]
+fn EditLink(cx: Scope, slug: String) -> impl IntoView {
+    let session = create_resource(cx, || (), move |_| get_session(cx));
+    let href = format!("/events/{slug}/edit");
+
+    let link = move || {
+        session
+            .with(cx, |session| {
+                matches!(session, Ok(Some(_))).then(|| view! {cx,
+                    <a href=href.clone() class="text-sm text-gray-500 hover:text-orange-600 underline">
+                        "Edit this meal"
+                    </a>
+                })
+            })
+            .flatten()
+    };
+
+    view! {
+        cx,
+        <Suspense fallback=|| ()>
+            {link}
+        </Suspense>
+    }
+}
+
+/// Content links to its images from the root of the site, like
+/// `services::feed` does.
+fn absolute_url(site_url: &str, url: &str) -> String {
+    if url.starts_with('/') {
+        format!("{}{url}", site_url.trim_end_matches('/'))
+    } else {
+        url.to_string()
+    }
+}
+
+fn not_found(cx: Scope) {
+    #[cfg(feature = "ssr")]
+    if let Some(response) = use_context::<leptos_axum::ResponseOptions>(cx) {
+        response.set_status(axum::http::StatusCode::NOT_FOUND);
+    }
+    #[cfg(not(feature = "ssr"))]
+    let _ = cx;
+}
@@ -2,30 +2,33 @@ use leptos::*;
 
 use crate::api;
 use crate::components::day::{Day, DayProps};
+use domain::DayOverview;
 
 #[component]
 pub fn HomePage(cx: Scope) -> impl IntoView {
     let events = create_resource(
         cx,
         || (),
-        |_| async { api::events::get_upcoming_events().await },
+        move |_| async move { api::events::get_upcoming_events(cx).await },
     );
 
     let events_view = move || {
         events.with(cx, |events| {
             events.clone().map(|event_overview| {
-                event_overview
-                    .events
-                    .iter()
+                let days = DayOverview::group(event_overview.events);
+                let day_count = days.len();
+
+                days.into_iter()
                     .enumerate()
-                    .map(|(index, event)| {
+                    .map(|(index, day)| {
                         view! {
                             cx,
                             <Day
-                                event=event.clone()
+                                date=day.date
+                                events=day.events
                                 next={Some(index == 0)}
                                 last={
-                                    if event_overview.events.len() - 1 == index {
+                                    if day_count - 1 == index {
                                         Some(true)
                                     } else {
                                         None
@@ -0,0 +1,84 @@
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+
+use crate::api::auth::*;
+use domain::AuthorSession;
+
+/// Where authors log in to change the plan, and log out again.
+#[component]
+pub fn LoginPage(cx: Scope) -> impl IntoView {
+    let login = create_server_action::<Login>(cx);
+    let logout = create_server_action::<Logout>(cx);
+    let session = create_resource(
+        cx,
+        move || (login.version().get(), logout.version().get()),
+        move |_| get_session(cx),
+    );
+
+    let session_view = move || {
+        session.with(cx, |session| match session.clone() {
+            Ok(Some(session)) => view! {cx, <LoggedIn session=session logout=logout />}.into_view(cx),
+            Ok(None) => view! {cx, <LoginForm login=login />}.into_view(cx),
+            Err(_) => view! {cx,
+                <p class="text-gray-500">"Could not check whether you are logged in."</p>
+            }
+            .into_view(cx),
+        })
+    };
+
+    view! {
+        cx,
+        <Title text="Log in · Bitebuds" />
+        <div class="space-y-4 pt-8 max-w-sm">
+            <Suspense fallback=move || view! {cx, <p>"Loading..."</p>}>
+                {session_view}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn LoginForm(cx: Scope, login: Action<Login, Result<AuthorSession, ServerFnError>>) -> impl IntoView {
+    let error = move || {
+        login.value().get().and_then(|result| result.err()).map(|e| view! {cx,
+            <p class="text-sm text-red-600">{e.to_string()}</p>
+        })
+    };
+
+    let input_class = "w-full border border-gray-300 rounded px-2 py-1 text-sm";
+
+    view! {
+        cx,
+        <h2 class="font-semibold text-lg">"Log in"</h2>
+        <ActionForm action=login>
+            <div class="space-y-2">
+                <input type="text" name="name" placeholder="Name" autocomplete="username" required=true class=input_class />
+                <input type="password" name="password" placeholder="Password" autocomplete="current-password" required=true class=input_class />
+                <button type="submit" class="bg-orange-600 hover:bg-orange-700 text-white text-sm rounded px-3 py-1">
+                    "Log in"
+                </button>
+                {error}
+            </div>
+        </ActionForm>
+    }
+}
+
+#[component]
+fn LoggedIn(
+    cx: Scope,
+    session: AuthorSession,
+    logout: Action<Logout, Result<(), ServerFnError>>,
+) -> impl IntoView {
+    view! {
+        cx,
+        <p>"You are logged in as " <span class="font-medium">{session.name}</span> "."</p>
+        <p><a href="/new" class="text-orange-600 hover:underline">"Plan a meal"</a></p>
+        <ActionForm action=logout>
+            <input type="hidden" name="csrf_token" value=session.csrf_token />
+            <button type="submit" class="text-sm text-gray-500 hover:text-gray-700 underline">
+                "Log out"
+            </button>
+        </ActionForm>
+    }
+}
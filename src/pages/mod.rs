@@ -1 +1,7 @@
+pub mod archive;
+pub mod calendar;
+pub mod editor;
+pub mod event;
 pub mod home;
+pub mod login;
+pub mod shopping;
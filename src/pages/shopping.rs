@@ -0,0 +1,151 @@
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+
+use crate::api::auth::get_session;
+use crate::api::shopping::*;
+use domain::units::format_quantity;
+use domain::{AuthorSession, ShoppingItem, ShoppingList};
+
+/// What to buy for this week. Logged in authors can check off what has
+/// been bought.
+#[component]
+pub fn ShoppingListPage(cx: Scope) -> impl IntoView {
+    let query = use_query_map(cx);
+    let check = create_server_action::<CheckShoppingItem>(cx);
+
+    let list = create_resource(
+        cx,
+        move || (query.with(|q| q.get("from").cloned()), check.version().get()),
+        move |(from, _)| async move {
+            let session = get_session(cx).await?;
+            let list = get_shopping_list(cx, from).await?;
+
+            Ok::<_, ServerFnError>((session, list))
+        },
+    );
+
+    let list_view = move || {
+        list.with(cx, |list| match list.clone() {
+            Ok((session, list)) => {
+                // There are no weeks before the first or after the last day.
+                let previous = list.from.checked_sub_signed(chrono::Duration::weeks(1));
+                let next = list.from.checked_add_signed(chrono::Duration::weeks(1));
+
+                view! {
+                    cx,
+                    <div class="flex justify-between items-baseline pb-2">
+                        <span>
+                            {previous.map(|previous| view! {
+                                cx,
+                                <a
+                                    class="text-sm text-gray-500 hover:text-orange-600"
+                                    href=format!("/shopping-list?from={}", previous.format("%Y-%m-%d"))
+                                >
+                                    "← Week before"
+                                </a>
+                            })}
+                        </span>
+                        <h2 class="font-semibold text-lg text-gray-700">
+                            {format!("{} – {}", list.from.format("%-d %B"), list.until.format("%-d %B"))}
+                        </h2>
+                        <span>
+                            {next.map(|next| view! {
+                                cx,
+                                <a
+                                    class="text-sm text-gray-500 hover:text-orange-600"
+                                    href=format!("/shopping-list?from={}", next.format("%Y-%m-%d"))
+                                >
+                                    "Week after →"
+                                </a>
+                            })}
+                        </span>
+                    </div>
+                    <ShoppingItems session=session list=list check=check />
+                }
+                .into_view(cx)
+            }
+            Err(_) => view! {cx,
+                <p class="text-gray-500">"There is no shopping list for that day."</p>
+            }
+            .into_view(cx),
+        })
+    };
+
+    view! {
+        cx,
+        <Title text="Shopping list · Bitebuds" />
+        <div class="space-y-4 pt-8">
+            <Suspense fallback=move || view! {cx, <p>"Loading shopping list..."</p>}>
+                {list_view}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn ShoppingItems(
+    cx: Scope,
+    session: Option<AuthorSession>,
+    list: ShoppingList,
+    check: Action<CheckShoppingItem, Result<(), ServerFnError>>,
+) -> impl IntoView {
+    if list.items.is_empty() {
+        return view! {cx,
+            <p class="text-gray-500">"None of the meals planned for the week have a recipe."</p>
+        }
+        .into_view(cx);
+    }
+
+    let from = list.from.format("%Y-%m-%d").to_string();
+
+    view! {
+        cx,
+        <ul class="space-y-2">
+            {list.items.into_iter().map(|item| {
+                let toggle = session.clone().map(|session| view! {cx,
+                    <ActionForm action=check>
+                        <input type="hidden" name="csrf_token" value=session.csrf_token />
+                        <input type="hidden" name="from" value=from.clone() />
+                        <input type="hidden" name="key" value=item.key.clone() />
+                        <input type="hidden" name="checked" value=(!item.checked).to_string() />
+                        <button
+                            type="submit"
+                            class="text-orange-600"
+                            title={if item.checked { "Not bought yet" } else { "Bought" }}
+                        >
+                            {if item.checked { "☑" } else { "☐" }}
+                        </button>
+                    </ActionForm>
+                });
+
+                view! {cx,
+                    <li class="flex items-baseline gap-2">
+                        {toggle}
+                        <div>
+                            <span class={if item.checked { "line-through text-gray-400" } else { "" }}>
+                                {format_item(&item)}
+                            </span>
+                            <p class="text-xs text-gray-500">{item.recipes.join(", ")}</p>
+                        </div>
+                    </li>
+                }
+            }).collect::<Vec<_>>()}
+        </ul>
+    }
+    .into_view(cx)
+}
+
+fn format_item(item: &ShoppingItem) -> String {
+    let mut line = match (item.quantity, item.unit.as_ref()) {
+        (Some(quantity), Some(unit)) => format!("{} {unit} {}", format_quantity(quantity), item.name),
+        (Some(quantity), None) => format!("{} {}", format_quantity(quantity), item.name),
+        (None, _) => item.name.clone(),
+    };
+
+    if !item.notes.is_empty() {
+        line = format!("{line}, {}", item.notes.join("; "));
+    }
+
+    line
+}
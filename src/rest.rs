@@ -0,0 +1,182 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, NaiveDate};
+use domain::{Event, EventPage, MealSlot, Recipe, SortOrder};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use crate::state::AppState;
+
+/// Version 1 of the read-only REST api, served under `/api/v1`. Unlike the
+/// server functions its paths and json are kept stable.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Bitebuds", description = "The meal plan and its recipes."),
+    paths(list_events, get_event, get_recipe),
+    components(schemas(
+        domain::Event,
+        domain::EventOverview,
+        domain::EventPage,
+        domain::Image,
+        domain::Metadata,
+        domain::MealSlot,
+        domain::Recurrence,
+        domain::Recipe,
+        domain::Ingredient,
+        domain::Step,
+        domain::Reference,
+        domain::SortOrder,
+        ApiErrorBody,
+    ))
+)]
+pub struct ApiDoc;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0} not found")]
+    NotFound(&'static str),
+    #[error("something went wrong")]
+    Internal(#[from] eyre::Report),
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiErrorBody {
+    error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Internal(e) => {
+                tracing::warn!(error = e.to_string(), "api request failed");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+
+        (
+            status,
+            Json(ApiErrorBody {
+                error: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventsQuery {
+    /// First date to include, defaults to today.
+    from: Option<NaiveDate>,
+    /// Last date to include, defaults to twelve weeks from `from`.
+    until: Option<NaiveDate>,
+    order: Option<SortOrder>,
+    /// The `nextCursor` of the previous page.
+    cursor: Option<String>,
+    /// Events per page, at most 100.
+    limit: Option<usize>,
+    /// Only events with this tag.
+    tag: Option<String>,
+    /// Only events for this meal.
+    meal: Option<MealSlot>,
+}
+
+/// Events between two dates, with recurring events expanded into their
+/// occurrences.
+#[utoipa::path(
+    get,
+    path = "/api/v1/events",
+    params(EventsQuery),
+    responses(
+        (status = 200, description = "A page of events", body = EventPage),
+        (status = 400, description = "Invalid range or cursor", body = ApiErrorBody),
+    )
+)]
+pub async fn list_events(
+    Extension(state): Extension<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<EventPage>, ApiError> {
+    let from = query.from.unwrap_or_else(|| state.store.today());
+    let until = query
+        .until
+        .unwrap_or(from + Duration::weeks(services::RECURRENCE_WINDOW_WEEKS));
+
+    services::check_range(from, until).map_err(ApiError::BadRequest)?;
+
+    let events = state
+        .store
+        .get_events_between(from, until)
+        .await?
+        .into_iter()
+        .filter(|event| match &query.tag {
+            Some(tag) => event.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            None => true,
+        })
+        .filter(|event| query.meal.is_none() || event.meal == query.meal)
+        .collect();
+
+    let page = services::pagination::paginate(
+        events,
+        query.order.unwrap_or_default(),
+        query.cursor.as_deref(),
+        query.limit,
+    )
+    .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    Ok(Json(page))
+}
+
+/// A single event, or an occurrence of a recurring event.
+#[utoipa::path(
+    get,
+    path = "/api/v1/events/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the event")),
+    responses(
+        (status = 200, description = "The event", body = Event),
+        (status = 404, description = "No such event", body = ApiErrorBody),
+    )
+)]
+pub async fn get_event(
+    Extension(state): Extension<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<Event>, ApiError> {
+    state
+        .store
+        .get_event(id)
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound("event"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the recipe")),
+    responses(
+        (status = 200, description = "The recipe", body = Recipe),
+        (status = 404, description = "No such recipe", body = ApiErrorBody),
+    )
+)]
+pub async fn get_recipe(
+    Extension(state): Extension<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<Recipe>, ApiError> {
+    state
+        .store
+        .get_recipe(id)
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound("recipe"))
+}
+
+pub async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
@@ -0,0 +1,72 @@
+use axum::{
+    body::Body,
+    extract::{Extension, Path, RawQuery},
+    http::{header, HeaderMap, Request},
+    response::IntoResponse,
+};
+use leptos::{provide_context, Scope};
+use services::{Auth, EventStore};
+
+use crate::app::Analytics;
+
+/// The configured public url of the site, provided to server functions.
+#[derive(Clone, Debug)]
+pub struct SiteUrl(pub Option<String>);
+
+/// Everything the server hands to the app, both when rendering and when
+/// answering server functions.
+#[derive(Clone)]
+pub struct AppState {
+    pub store: EventStore,
+    pub auth: Auth,
+    pub analytics: Option<Analytics>,
+    /// Public url of the site, such as `https://bitebuds.example`, used for
+    /// links outside the site itself.
+    pub site_url: Option<String>,
+}
+
+impl AppState {
+    pub fn provide(&self, cx: Scope) {
+        provide_context(cx, self.store.clone());
+        provide_context(cx, self.auth.clone());
+        provide_context(cx, SiteUrl(self.site_url.clone()));
+        if let Some(analytics) = self.analytics.clone() {
+            provide_context(cx, analytics);
+        }
+    }
+}
+
+pub async fn server_fn_handler(
+    Extension(state): Extension<AppState>,
+    path: Path<String>,
+    headers: HeaderMap,
+    raw_query: RawQuery,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    leptos_axum::handle_server_fns_with_context(
+        path,
+        headers,
+        raw_query,
+        move |cx| state.provide(cx),
+        req,
+    )
+    .await
+}
+
+/// The configured site url, or else one guessed from the request.
+pub fn site_url(configured: Option<&str>, headers: &HeaderMap) -> String {
+    if let Some(site_url) = configured {
+        return site_url.to_string();
+    }
+
+    let host = headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .unwrap_or("localhost");
+    let scheme = headers
+        .get("x-forwarded-proto")
+        .and_then(|proto| proto.to_str().ok())
+        .unwrap_or("http");
+
+    format!("{scheme}://{host}")
+}